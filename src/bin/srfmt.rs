@@ -0,0 +1,301 @@
+//! Command-line tool for inspecting and converting files.
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use sr_formats::jmxvddj::JmxTexture;
+use sr_formats::scan::ScanReport;
+use sr_formats::span;
+use sr_formats::{parse_any, AnyFile};
+
+const USAGE: &str = "\
+usage: srfmt <command> [args]
+
+commands:
+    dump <file>                 prints the parsed file as pretty JSON
+    info <file>                 prints a summary of the parsed file
+    spans <file>                prints a hexdump of the file coloured by the fields the bytes
+                                were read as, green for known, yellow for unknown fields and
+                                red for bytes no parser read
+    convert <input> <output>    converts between the formats given by the extensions:
+                                    any -> .json
+                                    .json -> .bms, .ddj
+                                    .ddj -> .png (first mip level), .dds
+                                    .bsr -> .glb
+    scan <dir>                  parses all files below the directory and reports the failures
+
+options:
+    --root <dir>    the directory the paths referenced by a .bsr are relative to, defaults to
+                    the current directory
+    --report <file>     scan: writes the report as JSON to the file
+    --baseline <file>   scan: lists the files whose results changed since the given report";
+
+type BoxError = Box<dyn Error>;
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        },
+    }
+}
+
+fn run(args: &[String]) -> Result<(), BoxError> {
+    let mut root = PathBuf::from(".");
+    let mut report = None;
+    let mut baseline = None;
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--root" => root = args.next().ok_or("--root expects a directory")?.into(),
+            "--report" => report = Some(args.next().ok_or("--report expects a file")?),
+            "--baseline" => baseline = Some(args.next().ok_or("--baseline expects a file")?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            },
+            _ => positional.push(arg.as_str()),
+        }
+    }
+    match positional[..] {
+        ["dump", file] => {
+            let parsed = parse_file(Path::new(file))?;
+            let mut stdout = io::stdout().lock();
+            serde_json::to_writer_pretty(&mut stdout, &parsed)?;
+            Ok(writeln!(stdout)?)
+        },
+        ["info", file] => {
+            let parsed = parse_file(Path::new(file))?;
+            Ok(io::stdout().write_all(info(&parsed).as_bytes())?)
+        },
+        ["spans", file] => {
+            let data = fs::read(file)?;
+            let (parsed, spans) = span::record(&data, parse_any);
+            parsed?;
+            Ok(io::stdout().write_all(span::annotated_hexdump(&data, &spans).as_bytes())?)
+        },
+        ["convert", input, output] => convert(Path::new(input), Path::new(output), &root),
+        ["scan", dir] => scan(
+            Path::new(dir),
+            report.map(Path::new),
+            baseline.map(Path::new),
+        ),
+        _ => Err(USAGE.into()),
+    }
+}
+
+fn parse_file(path: &Path) -> Result<AnyFile, BoxError> {
+    let data = fs::read(path)?;
+    Ok(parse_any(&data)?)
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+fn convert(input: &Path, output: &Path, root: &Path) -> Result<(), BoxError> {
+    let data = match (extension(input).as_str(), extension(output).as_str()) {
+        ("json", "bms" | "ddj") => {
+            let parsed: AnyFile = serde_json::from_slice(&fs::read(input)?)?;
+            let mut data = Vec::new();
+            match parsed {
+                AnyFile::JmxBMesh(mesh) => mesh.write(&mut data)?,
+                AnyFile::JmxTexture(texture) => texture.write(&mut data)?,
+                other => return Err(format!("{:?} files can't be written", other.kind()).into()),
+            }
+            data
+        },
+        (_, "json") => serde_json::to_vec_pretty(&parse_file(input)?)?,
+        ("ddj", "png") => {
+            let texture = JmxTexture::parse(&fs::read(input)?)?;
+            let levels = texture.decode()?;
+            levels
+                .first()
+                .ok_or("the texture has no mip levels")?
+                .to_png()?
+        },
+        ("ddj", "dds") => JmxTexture::parse(&fs::read(input)?)?.data,
+        ("bsr", "glb") => {
+            let res = sr_formats::jmxvbsr::JmxRes::parse(&fs::read(input)?)?;
+            sr_formats::gltf::export(&res, |path| load(root, path))?
+        },
+        (from, to) => return Err(format!("can't convert from .{} to .{}", from, to).into()),
+    };
+    fs::write(output, data)?;
+    Ok(())
+}
+
+fn scan(dir: &Path, report_path: Option<&Path>, baseline: Option<&Path>) -> Result<(), BoxError> {
+    let report = ScanReport::scan_dir(dir)?;
+    let mut out = io::stdout().lock();
+    writeln!(
+        out,
+        "scanned {} files, {} empty",
+        report.files, report.empty
+    )?;
+    for (kind, stats) in &report.formats {
+        writeln!(
+            out,
+            "{:?}: {}/{} parsed ({:.2}%)",
+            kind,
+            stats.parsed,
+            stats.parsed + stats.failed(),
+            stats.success_rate() * 100.0
+        )?;
+        for (location, group) in &stats.failures {
+            writeln!(out, "  {} ({} files)", location, group.files.len())?;
+            for (path, offset) in group.files.iter().take(5) {
+                writeln!(out, "    {} at {:#X}", path.display(), offset)?;
+            }
+            if group.files.len() > 5 {
+                writeln!(out, "    ...")?;
+            }
+            for line in group.hexdump.lines() {
+                writeln!(out, "    {}", line)?;
+            }
+        }
+    }
+    for (magic, count) in &report.unsupported {
+        writeln!(out, "unsupported {}: {}", magic, count)?;
+    }
+    let undetected = report
+        .undetected
+        .iter()
+        .map(|(ext, count)| format!("{} ({})", ext, count))
+        .collect::<Vec<_>>();
+    writeln!(out, "undetected extensions: {}", undetected.join(", "))?;
+    for (path, error) in &report.io_errors {
+        writeln!(out, "failed to read {}: {}", path.display(), error)?;
+    }
+
+    if let Some(baseline) = baseline {
+        let baseline: ScanReport = serde_json::from_slice(&fs::read(baseline)?)?;
+        let diff = report.diff(&baseline);
+        for path in &diff.fixed {
+            writeln!(out, "fixed: {}", path.display())?;
+        }
+        for path in &diff.broken {
+            writeln!(out, "broken: {}", path.display())?;
+        }
+    }
+    if let Some(report_path) = report_path {
+        fs::write(report_path, serde_json::to_vec_pretty(&report)?)?;
+    }
+    Ok(())
+}
+
+/// Loads a file referenced with the client's `\` separators from below `root`.
+fn load(root: &Path, path: &Path) -> io::Result<Vec<u8>> {
+    let relative = path.to_string_lossy().replace('\\', "/");
+    fs::read(root.join(relative))
+}
+
+fn info(file: &AnyFile) -> String {
+    let kind = file.kind();
+    let details = match file {
+        AnyFile::JmxAnimation(animation) => format!(
+            "name: {}\nduration: {}\nkey frames: {}\nanimated bones: {}\n",
+            animation.name,
+            animation.duration,
+            animation.key_frame_times.len(),
+            animation.animated_bones.len()
+        ),
+        AnyFile::JmxBMesh(mesh) => format!(
+            "name: {}\nmaterial: {}\nvertices: {}\nfaces: {}\nbones: {}\nnav mesh: {}\n",
+            mesh.header.name,
+            mesh.header.material,
+            mesh.vertices.len(),
+            mesh.faces.len(),
+            mesh.bone_data.as_ref().map_or(0, |(names, _)| names.len()),
+            mesh.nav_mesh.is_some()
+        ),
+        AnyFile::JmxMat(mat) => {
+            let names = mat.0.iter().map(|m| &*m.name).collect::<Vec<_>>();
+            format!("materials: {}\n", names.join(", "))
+        },
+        AnyFile::JmxSkeleton(skeleton) => format!("bones: {}\n", skeleton.bones.len()),
+        AnyFile::JmxRes(res) => {
+            let palette_paths = res
+                .mod_palette
+                .as_ref()
+                .map_or(0, |palette| palette.paths.len());
+            format!(
+                "name: {}\ntype: {:?}\nmaterial sets: {}\nmeshes: {}\nskeletons: {}\n\
+                 animations: {}\nmod palette paths: {}\n",
+                res.header.name,
+                res.header.res_type,
+                res.material_sets.len(),
+                res.mesh_paths.len(),
+                res.skeleton_paths.len(),
+                res.animation.paths.len(),
+                palette_paths
+            )
+        },
+        AnyFile::JmxCompound(compound) => format!(
+            "name: {}\nresources: {}\n",
+            compound.header.name,
+            compound.resource_paths.len()
+        ),
+        AnyFile::JmxTexture(texture) => match texture.dds_header() {
+            Ok(header) => format!(
+                "size: {}x{}\nformat: {:?}\nmip levels: {}\n",
+                header.width,
+                header.height,
+                header.pixel_format.format(),
+                header.mip_levels()
+            ),
+            Err(e) => format!("invalid DDS header: {}\n", e),
+        },
+        AnyFile::JmxDungeon(dungeon) => format!(
+            "name: {}\nroom objects: {}\n",
+            dungeon.header.dungeon_name,
+            dungeon.room_objects.len()
+        ),
+        AnyFile::JmxMapMesh(mesh) => {
+            let heights = mesh
+                .blocks
+                .iter()
+                .flat_map(|b| b.cells.iter().map(|c| c.height));
+            let (min, max) = heights.fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), h| {
+                (min.min(h), max.max(h))
+            });
+            format!(
+                "blocks: {}\nheights: {} to {}\n",
+                mesh.blocks.len(),
+                min,
+                max
+            )
+        },
+        AnyFile::JmxMapObject(map_object) => format!(
+            "groups: {}\nobjects: {}\n",
+            map_object.objects.len(),
+            map_object
+                .objects
+                .iter()
+                .map(|group| group.entries.len())
+                .sum::<usize>()
+        ),
+        AnyFile::JmxNvm(nvm) => format!(
+            "entries: {}\ncells: {}\nregion links: {}\ncell links: {}\n",
+            nvm.nav_entries.len(),
+            nvm.nav_cells.len(),
+            nvm.nav_region_links.len(),
+            nvm.nav_cell_links.len()
+        ),
+        AnyFile::ObjectIfo(objects) => format!("entries: {}\n", objects.len()),
+        AnyFile::ObjectStringIfo(objects) => format!("entries: {}\n", objects.len()),
+        AnyFile::TileInfo2D(tiles) => format!("entries: {}\n", tiles.len()),
+        _ => String::new(),
+    };
+    format!("format: {:?}\n{}", kind, details)
+}
@@ -0,0 +1,708 @@
+//! The DDS files embedded in [`JmxTexture`](crate::jmxvddj::JmxTexture)s and converting them from
+//! and to RGBA8.
+use bitflags::bitflags;
+use nom::bytes::complete::{tag, take};
+use nom::combinator::{map, verify};
+use nom::number::complete::le_u32;
+use nom::sequence::{preceded, terminated, tuple};
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use crate::error::{finish, ErrorKind, IResult, ParseError, Result};
+use crate::parser_ext::complete::take_fixed;
+use crate::parser_ext::multi::count_indexed;
+use crate::span::context;
+use crate::{fields, ttr_closure};
+
+bitflags! {
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+    #[cfg_attr(feature = "serde", serde(transparent))]
+    pub struct PixelFormatFlags: u32 {
+        const ALPHA_PIXELS = 0x1;
+        const ALPHA =        0x2;
+        const FOUR_CC =      0x4;
+        const RGB =          0x40;
+        const YUV =          0x200;
+        const LUMINANCE =    0x20000;
+    }
+}
+
+const DDSD_CAPS: u32 = 0x1;
+const DDSD_HEIGHT: u32 = 0x2;
+const DDSD_WIDTH: u32 = 0x4;
+const DDSD_PIXELFORMAT: u32 = 0x1000;
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDSD_LINEARSIZE: u32 = 0x80000;
+const DDSCAPS_COMPLEX: u32 = 0x8;
+const DDSCAPS_TEXTURE: u32 = 0x1000;
+const DDSCAPS_MIPMAP: u32 = 0x400000;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DdsPixelFormat {
+    pub flags: PixelFormatFlags,
+    pub four_cc: [u8; 4],
+    pub rgb_bit_count: u32,
+    pub r_mask: u32,
+    pub g_mask: u32,
+    pub b_mask: u32,
+    pub a_mask: u32,
+}
+
+impl DdsPixelFormat {
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "DdsPixelFormat",
+            map(
+                preceded(
+                    verify(le_u32, |&size| size == 32),
+                    fields!(
+                        flags: map(le_u32, PixelFormatFlags::from_bits_truncate),
+                        four_cc: take_fixed::<4>,
+                        rgb_bit_count: le_u32,
+                        r_mask: le_u32,
+                        g_mask: le_u32,
+                        b_mask: le_u32,
+                        a_mask: le_u32,
+                    ),
+                ),
+                ttr_closure! {
+                    DdsPixelFormat {
+                        flags,
+                        four_cc,
+                        rgb_bit_count,
+                        r_mask,
+                        g_mask,
+                        b_mask,
+                        a_mask,
+                    }
+                },
+            ),
+        )(i)
+    }
+
+    /// The format of the pixel data if it is one the decoder supports.
+    pub fn format(&self) -> Option<DdsFormat> {
+        if self.flags.contains(PixelFormatFlags::FOUR_CC) {
+            match &self.four_cc {
+                b"DXT1" => Some(DdsFormat::Dxt1),
+                b"DXT2" | b"DXT3" => Some(DdsFormat::Dxt3),
+                b"DXT4" | b"DXT5" => Some(DdsFormat::Dxt5),
+                _ => None,
+            }
+        } else if self.flags.contains(PixelFormatFlags::RGB)
+            && matches!(self.rgb_bit_count, 8 | 16 | 24 | 32)
+        {
+            let a_mask = if self.flags.contains(PixelFormatFlags::ALPHA_PIXELS) {
+                self.a_mask
+            } else {
+                0
+            };
+            Some(DdsFormat::Uncompressed {
+                bit_count: self.rgb_bit_count,
+                masks: [self.r_mask, self.g_mask, self.b_mask, a_mask],
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// The pixel formats supported by [`decode`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DdsFormat {
+    /// BC1
+    Dxt1,
+    /// BC2
+    Dxt3,
+    /// BC3
+    Dxt5,
+    /// Pixels of `bit_count` bits with the channels selected by the RGBA `masks`, a zero alpha
+    /// mask meaning opaque.
+    Uncompressed { bit_count: u32, masks: [u32; 4] },
+}
+
+impl DdsFormat {
+    /// The amount of bytes a mip level of the given dimensions occupies.
+    fn level_size(self, width: u32, height: u32) -> u64 {
+        let blocks = || u64::from(width.div_ceil(4)) * u64::from(height.div_ceil(4));
+        match self {
+            DdsFormat::Dxt1 => blocks() * 8,
+            DdsFormat::Dxt3 | DdsFormat::Dxt5 => blocks() * 16,
+            DdsFormat::Uncompressed { bit_count, .. } => {
+                u64::from(width) * u64::from(height) * u64::from(bit_count / 8)
+            },
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DdsHeader {
+    pub flags: u32,
+    pub height: u32,
+    pub width: u32,
+    pub pitch_or_linear_size: u32,
+    pub depth: u32,
+    pub mip_map_count: u32,
+    pub pixel_format: DdsPixelFormat,
+    pub caps: u32,
+    pub caps2: u32,
+    pub caps3: u32,
+    pub caps4: u32,
+}
+
+impl DdsHeader {
+    /// Parses the header of a DDS file, including its `DDS ` magic.
+    pub fn parse(i: &[u8]) -> Result<Self> {
+        finish("DdsHeader", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "DdsHeader",
+            map(
+                preceded(
+                    tuple((tag(b"DDS "), verify(le_u32, |&size| size == 124))),
+                    fields!(
+                        flags: le_u32,
+                        height: le_u32,
+                        width: le_u32,
+                        pitch_or_linear_size: le_u32,
+                        depth: le_u32,
+                        mip_map_count: le_u32,
+                        // reserved
+                        pixel_format: preceded(take(4 * 11usize), DdsPixelFormat::parse),
+                        caps: le_u32,
+                        caps2: le_u32,
+                        caps3: le_u32,
+                        // reserved
+                        caps4: terminated(le_u32, le_u32),
+                    ),
+                ),
+                ttr_closure! {
+                    DdsHeader {
+                        flags,
+                        height,
+                        width,
+                        pitch_or_linear_size,
+                        depth,
+                        mip_map_count,
+                        pixel_format,
+                        caps,
+                        caps2,
+                        caps3,
+                        caps4,
+                    }
+                },
+            ),
+        )(i)
+    }
+
+    /// The amount of mip levels stored in the file, capped to the amount the dimensions allow.
+    pub fn mip_levels(&self) -> u32 {
+        let max_levels = 32 - self.width.max(self.height).max(1).leading_zeros();
+        if self.flags & DDSD_MIPMAPCOUNT != 0 {
+            self.mip_map_count.clamp(1, max_levels)
+        } else {
+            1
+        }
+    }
+
+    /// The dimensions of the given mip level.
+    pub fn level_dimensions(&self, level: u32) -> (u32, u32) {
+        ((self.width >> level).max(1), (self.height >> level).max(1))
+    }
+}
+
+/// An image of 8-bit RGBA pixels stored row by row.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RgbaImage {
+    pub width: u32,
+    pub height: u32,
+    pub data: Box<[u8]>,
+}
+
+impl RgbaImage {
+    fn new(width: u32, height: u32) -> Self {
+        RgbaImage {
+            width,
+            height,
+            data: vec![0; width as usize * height as usize * 4].into_boxed_slice(),
+        }
+    }
+
+    fn put(&mut self, x: u32, y: u32, rgba: [u8; 4]) {
+        if x < self.width && y < self.height {
+            let idx = (y as usize * self.width as usize + x as usize) * 4;
+            self.data[idx..idx + 4].copy_from_slice(&rgba);
+        }
+    }
+}
+
+/// Decodes all mip levels of a DDS file, largest first.
+pub fn decode(i: &[u8]) -> Result<Box<[RgbaImage]>> {
+    finish("Dds", i, decode_nom)
+}
+
+pub(crate) fn decode_nom(i: &[u8]) -> IResult<&[u8], Box<[RgbaImage]>> {
+    let (data, header) = DdsHeader::parse_nom(i)?;
+    let format = header.pixel_format.format().ok_or_else(|| {
+        nom::Err::Failure(ParseError::new(
+            i,
+            ErrorKind::External("unsupported DDS pixel format".into()),
+        ))
+    })?;
+    context(
+        "mip_levels",
+        count_indexed(
+            move |i, level| {
+                let (width, height) = header.level_dimensions(level as u32);
+                let size = format.level_size(width, height);
+                let size = usize::try_from(size).unwrap_or(usize::MAX);
+                map(take(size), move |data| {
+                    decode_level(format, width, height, data)
+                })(i)
+            },
+            header.mip_levels() as usize,
+        ),
+    )(data)
+}
+
+fn decode_level(format: DdsFormat, width: u32, height: u32, data: &[u8]) -> RgbaImage {
+    let mut image = RgbaImage::new(width, height);
+    let block_size = match format {
+        DdsFormat::Uncompressed { bit_count, masks } => {
+            let pixel_size = bit_count as usize / 8;
+            for (idx, pixel) in data.chunks_exact(pixel_size).enumerate() {
+                let pixel = pixel
+                    .iter()
+                    .rev()
+                    .fold(0u32, |acc, &b| (acc << 8) | u32::from(b));
+                let [r, g, b, a] = masks;
+                let alpha = if a == 0 { 255 } else { channel(pixel, a) };
+                let (x, y) = (idx as u32 % width, idx as u32 / width);
+                image.put(
+                    x,
+                    y,
+                    [
+                        channel(pixel, r),
+                        channel(pixel, g),
+                        channel(pixel, b),
+                        alpha,
+                    ],
+                );
+            }
+            return image;
+        },
+        DdsFormat::Dxt1 => 8,
+        DdsFormat::Dxt3 | DdsFormat::Dxt5 => 16,
+    };
+    let blocks_per_row = width.div_ceil(4);
+    for (idx, block) in data.chunks_exact(block_size).enumerate() {
+        let mut pixels = match format {
+            DdsFormat::Dxt1 => decode_color_block(block, false),
+            _ => decode_color_block(&block[8..], true),
+        };
+        match format {
+            DdsFormat::Dxt3 => decode_explicit_alpha(&block[..8], &mut pixels),
+            DdsFormat::Dxt5 => decode_interpolated_alpha(&block[..8], &mut pixels),
+            _ => (),
+        }
+        let (bx, by) = (
+            idx as u32 % blocks_per_row * 4,
+            idx as u32 / blocks_per_row * 4,
+        );
+        for (p, rgba) in pixels.into_iter().enumerate() {
+            image.put(bx + p as u32 % 4, by + p as u32 / 4, rgba);
+        }
+    }
+    image
+}
+
+/// Extracts the channel selected by `mask` from `pixel`, scaled to 8 bits.
+fn channel(pixel: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let value = u64::from((pixel & mask) >> mask.trailing_zeros());
+    let max = (1u64 << mask.count_ones()) - 1;
+    (value * 255 / max) as u8
+}
+
+fn rgb565(color: u16) -> [u8; 4] {
+    let r = (color >> 11) as u8 & 0x1F;
+    let g = (color >> 5) as u8 & 0x3F;
+    let b = color as u8 & 0x1F;
+    [
+        (r << 3) | (r >> 2),
+        (g << 2) | (g >> 4),
+        (b << 3) | (b >> 2),
+        255,
+    ]
+}
+
+/// The four colors a color block can select from.
+fn color_palette(c0: u16, c1: u16, four_colors: bool) -> [[u8; 4]; 4] {
+    let (p0, p1) = (rgb565(c0), rgb565(c1));
+    let mix = |w0: u16, w1: u16| {
+        let mut c = [0, 0, 0, 255];
+        for ch in 0..3 {
+            c[ch] = ((u16::from(p0[ch]) * w0 + u16::from(p1[ch]) * w1) / (w0 + w1)) as u8;
+        }
+        c
+    };
+    if four_colors {
+        [p0, p1, mix(2, 1), mix(1, 2)]
+    } else {
+        [p0, p1, mix(1, 1), [0, 0, 0, 0]]
+    }
+}
+
+/// Decodes the 8-byte color part of a block. BC2 and BC3 always use the four color mode.
+fn decode_color_block(block: &[u8], always_four_colors: bool) -> [[u8; 4]; 16] {
+    let c0 = u16::from_le_bytes([block[0], block[1]]);
+    let c1 = u16::from_le_bytes([block[2], block[3]]);
+    let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    let palette = color_palette(c0, c1, always_four_colors || c0 > c1);
+    let mut pixels = [[0; 4]; 16];
+    for (p, pixel) in pixels.iter_mut().enumerate() {
+        *pixel = palette[(indices >> (p * 2)) as usize & 0b11];
+    }
+    pixels
+}
+
+/// BC2 stores 4 bits of alpha per pixel.
+fn decode_explicit_alpha(block: &[u8], pixels: &mut [[u8; 4]; 16]) {
+    for (p, pixel) in pixels.iter_mut().enumerate() {
+        let nibble = (block[p / 2] >> (p % 2 * 4)) & 0xF;
+        pixel[3] = nibble * 17;
+    }
+}
+
+/// The eight alpha values a BC3 alpha block can select from.
+fn alpha_palette(a0: u8, a1: u8) -> [u8; 8] {
+    let (a0, a1) = (u32::from(a0), u32::from(a1));
+    let mut palette = [a0 as u8, a1 as u8, 0, 0, 0, 0, 0, 0];
+    if a0 > a1 {
+        for i in 1..7 {
+            palette[i as usize + 1] = (((7 - i) * a0 + i * a1) / 7) as u8;
+        }
+    } else {
+        for i in 1..5 {
+            palette[i as usize + 1] = (((5 - i) * a0 + i * a1) / 5) as u8;
+        }
+        palette[7] = 255;
+    }
+    palette
+}
+
+/// BC3 interpolates alpha between two endpoints with 3-bit indices.
+fn decode_interpolated_alpha(block: &[u8], pixels: &mut [[u8; 4]; 16]) {
+    let palette = alpha_palette(block[0], block[1]);
+    let indices = block[2..8]
+        .iter()
+        .rev()
+        .fold(0u64, |acc, &b| (acc << 8) | u64::from(b));
+    for (p, pixel) in pixels.iter_mut().enumerate() {
+        pixel[3] = palette[(indices >> (p * 3)) as usize & 0b111];
+    }
+}
+
+/// The compressed formats supported by [`encode`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EncodeFormat {
+    /// BC1, one bit of alpha
+    Dxt1,
+    /// BC3, interpolated alpha
+    Dxt5,
+}
+
+impl RgbaImage {
+    /// The pixel at the given position clamped to the image, transparent black for empty images.
+    fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        if self.width == 0 || self.height == 0 {
+            return [0; 4];
+        }
+        let (x, y) = (x.min(self.width - 1), y.min(self.height - 1));
+        let idx = (y as usize * self.width as usize + x as usize) * 4;
+        [
+            self.data[idx],
+            self.data[idx + 1],
+            self.data[idx + 2],
+            self.data[idx + 3],
+        ]
+    }
+
+    /// Halves the image with a box filter, the edge pixels of odd dimensions being repeated.
+    fn downsample(&self) -> RgbaImage {
+        let mut image = RgbaImage::new((self.width / 2).max(1), (self.height / 2).max(1));
+        for y in 0..image.height {
+            for x in 0..image.width {
+                let mut sum = [0u32; 4];
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let pixel = self.pixel(x * 2 + dx, y * 2 + dy);
+                    sum.iter_mut()
+                        .zip(pixel)
+                        .for_each(|(s, c)| *s += u32::from(c));
+                }
+                image.put(x, y, sum.map(|s| ((s + 2) / 4) as u8));
+            }
+        }
+        image
+    }
+
+    /// Returns this image followed by all of its mip levels down to 1x1, empty images have no mip
+    /// levels.
+    pub fn mip_chain(&self) -> Vec<RgbaImage> {
+        let mut levels = vec![self.clone()];
+        if self.width == 0 || self.height == 0 {
+            return levels;
+        }
+        while let Some(last) = levels.last().filter(|it| it.width > 1 || it.height > 1) {
+            levels.push(last.downsample());
+        }
+        levels
+    }
+
+    /// Encodes the image as a PNG, failing for empty images which PNG can't represent.
+    #[cfg(feature = "png")]
+    pub fn to_png(&self) -> std::result::Result<Vec<u8>, png::EncodingError> {
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.data)?;
+        writer.finish()?;
+        Ok(png)
+    }
+}
+
+/// Encodes the given mip levels, largest first, into a DDS file.
+///
+/// The levels are expected to halve in size each, as produced by [`RgbaImage::mip_chain`].
+pub fn encode(levels: &[RgbaImage], format: EncodeFormat) -> Vec<u8> {
+    let (four_cc, dds_format) = match format {
+        EncodeFormat::Dxt1 => (*b"DXT1", DdsFormat::Dxt1),
+        EncodeFormat::Dxt5 => (*b"DXT5", DdsFormat::Dxt5),
+    };
+    let (width, height) = levels.first().map_or((0, 0), |it| (it.width, it.height));
+    let has_mips = levels.len() > 1;
+    let header = DdsHeader {
+        flags: DDSD_CAPS
+            | DDSD_HEIGHT
+            | DDSD_WIDTH
+            | DDSD_PIXELFORMAT
+            | DDSD_LINEARSIZE
+            | if has_mips { DDSD_MIPMAPCOUNT } else { 0 },
+        height,
+        width,
+        pitch_or_linear_size: dds_format.level_size(width, height) as u32,
+        depth: 0,
+        mip_map_count: if has_mips { levels.len() as u32 } else { 0 },
+        pixel_format: DdsPixelFormat {
+            flags: PixelFormatFlags::FOUR_CC,
+            four_cc,
+            rgb_bit_count: 0,
+            r_mask: 0,
+            g_mask: 0,
+            b_mask: 0,
+            a_mask: 0,
+        },
+        caps: DDSCAPS_TEXTURE
+            | if has_mips {
+                DDSCAPS_COMPLEX | DDSCAPS_MIPMAP
+            } else {
+                0
+            },
+        caps2: 0,
+        caps3: 0,
+        caps4: 0,
+    };
+
+    let mut out = Vec::new();
+    header.write(&mut out);
+    for level in levels {
+        for by in (0..level.height).step_by(4) {
+            for bx in (0..level.width).step_by(4) {
+                let mut pixels = [[0; 4]; 16];
+                for (p, pixel) in pixels.iter_mut().enumerate() {
+                    *pixel = level.pixel(bx + p as u32 % 4, by + p as u32 / 4);
+                }
+                if format == EncodeFormat::Dxt5 {
+                    encode_alpha_block(&pixels, &mut out);
+                }
+                encode_color_block(&pixels, format == EncodeFormat::Dxt1, &mut out);
+            }
+        }
+    }
+    out
+}
+
+impl DdsHeader {
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(b"DDS ");
+        let pf = &self.pixel_format;
+        let fields = [124, self.flags, self.height, self.width]
+            .into_iter()
+            .chain([self.pitch_or_linear_size, self.depth, self.mip_map_count])
+            .chain([0; 11])
+            .chain([32, pf.flags.bits(), u32::from_le_bytes(pf.four_cc)])
+            .chain([pf.rgb_bit_count, pf.r_mask, pf.g_mask, pf.b_mask, pf.a_mask])
+            .chain([self.caps, self.caps2, self.caps3, self.caps4, 0]);
+        fields.for_each(|v| out.extend_from_slice(&v.to_le_bytes()));
+    }
+}
+
+fn to_rgb565(c: [u8; 4]) -> u16 {
+    (u16::from(c[0]) >> 3) << 11 | (u16::from(c[1]) >> 2) << 5 | u16::from(c[2]) >> 3
+}
+
+fn distance(a: [u8; 4], b: [u8; 4]) -> u32 {
+    (0..3)
+        .map(|ch| (i32::from(a[ch]) - i32::from(b[ch])).pow(2) as u32)
+        .sum()
+}
+
+/// Encodes the colors of a block, using the endpoints of the block's bounding box diagonal.
+///
+/// With `punch_through` set, pixels with an alpha below 128 are encoded as transparent using the
+/// three color mode of BC1.
+fn encode_color_block(pixels: &[[u8; 4]; 16], punch_through: bool, out: &mut Vec<u8>) {
+    let transparent = |p: &[u8; 4]| punch_through && p[3] < 128;
+    let (mut min, mut max) = ([255u8; 4], [0u8; 4]);
+    for pixel in pixels.iter().filter(|p| !transparent(p)) {
+        for ch in 0..3 {
+            min[ch] = min[ch].min(pixel[ch]);
+            max[ch] = max[ch].max(pixel[ch]);
+        }
+    }
+    let (mut c0, mut c1) = (to_rgb565(max), to_rgb565(min));
+    let has_transparency = pixels.iter().any(transparent);
+    // the ordering of the endpoints selects the mode
+    if has_transparency == (c0 > c1) {
+        std::mem::swap(&mut c0, &mut c1);
+    }
+    let four_colors = c0 > c1;
+    let palette = color_palette(c0, c1, four_colors);
+    let mut indices = 0u32;
+    for (p, pixel) in pixels.iter().enumerate() {
+        let index = if transparent(pixel) {
+            3
+        } else {
+            let candidates = if four_colors { 0..4 } else { 0..3 };
+            candidates
+                .min_by_key(|&i| distance(palette[i], *pixel))
+                .unwrap_or(0)
+        };
+        indices |= (index as u32) << (p * 2);
+    }
+    out.extend_from_slice(&c0.to_le_bytes());
+    out.extend_from_slice(&c1.to_le_bytes());
+    out.extend_from_slice(&indices.to_le_bytes());
+}
+
+fn encode_alpha_block(pixels: &[[u8; 4]; 16], out: &mut Vec<u8>) {
+    let a0 = pixels.iter().map(|p| p[3]).max().unwrap_or(255);
+    let a1 = pixels.iter().map(|p| p[3]).min().unwrap_or(255);
+    let palette = alpha_palette(a0, a1);
+    let mut indices = 0u64;
+    for (p, pixel) in pixels.iter().enumerate() {
+        let index = (0..8)
+            .min_by_key(|&i| (i32::from(palette[i]) - i32::from(pixel[3])).abs())
+            .unwrap_or(0);
+        indices |= (index as u64) << (p * 3);
+    }
+    out.extend_from_slice(&[a0, a1]);
+    out.extend_from_slice(&indices.to_le_bytes()[..6]);
+}
+
+#[cfg(test)]
+fn test_dds(width: u32, height: u32, pixel_format: [u32; 8], data: &[u8]) -> Vec<u8> {
+    let mut file = b"DDS ".to_vec();
+    let mut header = [0u32; 31];
+    header[0] = 124;
+    header[2] = height;
+    header[3] = width;
+    header[18..26].copy_from_slice(&pixel_format);
+    header
+        .iter()
+        .for_each(|v| file.extend_from_slice(&v.to_le_bytes()));
+    file.extend_from_slice(data);
+    file
+}
+
+#[test]
+fn decode_dxt1_and_uncompressed() {
+    // red and blue endpoints, the first row picking each palette entry once
+    let dxt1 = test_dds(
+        4,
+        4,
+        [32, 0x4, u32::from_le_bytes(*b"DXT1"), 0, 0, 0, 0, 0],
+        &[0x00, 0xF8, 0x1F, 0x00, 0b11_10_01_00, 0, 0, 0],
+    );
+    let images = decode(&dxt1).unwrap();
+    assert_eq!(images.len(), 1);
+    assert_eq!(
+        &images[0].data[..16],
+        &[255, 0, 0, 255, 0, 0, 255, 255, 170, 0, 85, 255, 85, 0, 170, 255]
+    );
+
+    let argb = test_dds(
+        2,
+        1,
+        [32, 0x41, 0, 32, 0xFF_0000, 0xFF00, 0xFF, 0xFF00_0000],
+        &[0x30, 0x20, 0x10, 0x80, 1, 2, 3, 4],
+    );
+    let images = decode(&argb).unwrap();
+    assert_eq!(&*images[0].data, &[0x10, 0x20, 0x30, 0x80, 3, 2, 1, 4]);
+}
+
+#[test]
+fn encode_round_trip() {
+    let mut image = RgbaImage::new(8, 6);
+    for y in 0..6 {
+        for x in 0..8 {
+            let alpha = if x < 4 { 255 } else { y as u8 * 4 };
+            image.put(x, y, [x as u8 * 32, 128, 255, alpha]);
+        }
+    }
+    let levels = image.mip_chain();
+    assert_eq!(levels.len(), 4);
+
+    let decoded = decode(&encode(&levels, EncodeFormat::Dxt5)).unwrap();
+    assert_eq!(decoded.len(), 4);
+    assert_eq!((decoded[3].width, decoded[3].height), (1, 1));
+    for (a, b) in decoded[0].data.iter().zip(image.data.iter()) {
+        assert!(a.abs_diff(*b) <= 24, "{} {}", a, b);
+    }
+
+    let decoded = decode(&encode(&levels[..1], EncodeFormat::Dxt1)).unwrap();
+    assert_eq!(decoded.len(), 1);
+    assert_eq!(decoded[0].pixel(0, 0)[3], 255);
+    assert_eq!(decoded[0].pixel(7, 5), [0, 0, 0, 0]);
+
+    let empty = RgbaImage::new(0, 4);
+    assert_eq!(empty.pixel(1, 1), [0; 4]);
+    assert_eq!(empty.mip_chain().len(), 1);
+    assert_eq!(encode(&[empty], EncodeFormat::Dxt1).len(), 128);
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn encode_png() {
+    let mut image = RgbaImage::new(2, 1);
+    image.put(1, 0, [1, 2, 3, 4]);
+    let png = image.to_png().unwrap();
+    let mut reader = png::Decoder::new(&png[..]).read_info().unwrap();
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data).unwrap();
+    assert_eq!((info.width, info.height), (2, 1));
+    assert_eq!(info.color_type, png::ColorType::Rgba);
+    assert_eq!(data, [0, 0, 0, 0, 1, 2, 3, 4]);
+    assert!(RgbaImage::new(0, 0).to_png().is_err());
+}
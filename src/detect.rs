@@ -0,0 +1,191 @@
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use crate::error::{Error, ErrorKind, Result};
+use crate::jmxv2dti::TileInfo2D;
+use crate::jmxvban::JmxAnimation;
+use crate::jmxvbms::JmxBMesh;
+use crate::jmxvbmt::JmxMat;
+use crate::jmxvbsk::JmxSkeleton;
+use crate::jmxvbsr::JmxRes;
+use crate::jmxvcpd::JmxCompound;
+use crate::jmxvddj::JmxTexture;
+use crate::jmxvdof::JmxDungeon;
+use crate::jmxveff::JmxvEff;
+use crate::jmxvenvi::JmxEnvironment;
+use crate::jmxvmapm::JmxMapMesh;
+use crate::jmxvmapo::JmxMapObject;
+use crate::jmxvmapt::JmxMapTexture;
+use crate::jmxvmfo::JmxMapInfo;
+use crate::jmxvnvm::JmxNvm;
+use crate::jmxvobji::{ObjectIfo, ObjectStringIfo};
+
+/// The formats that can be recognized by their `JMXV` magic, named after the type they parse into.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum FileKind {
+    JmxAnimation,
+    JmxBMesh,
+    JmxMat,
+    JmxSkeleton,
+    JmxRes,
+    JmxCompound,
+    JmxTexture,
+    JmxDungeon,
+    JmxvEff,
+    JmxEnvironment,
+    JmxMapMesh,
+    JmxMapObject,
+    JmxMapTexture,
+    JmxMapInfo,
+    JmxNvm,
+    ObjectIfo,
+    ObjectStringIfo,
+    TileInfo2D,
+}
+
+/// The magics including the versions supported by the parsers.
+const MAGICS: &[(&[u8], FileKind)] = &[
+    (b"JMXVBAN 0102", FileKind::JmxAnimation),
+    (b"JMXVBMS 0109", FileKind::JmxBMesh),
+    (b"JMXVBMS 0110", FileKind::JmxBMesh),
+    (b"JMXVBMT 0102", FileKind::JmxMat),
+    (b"JMXVBSK 0101", FileKind::JmxSkeleton),
+    (b"JMXVRES 0107", FileKind::JmxRes),
+    (b"JMXVRES 0108", FileKind::JmxRes),
+    (b"JMXVRES 0109", FileKind::JmxRes),
+    (b"JMXVCPD 0101", FileKind::JmxCompound),
+    (b"JMXVDDJ 1000", FileKind::JmxTexture),
+    (b"JMXVDOF 0101", FileKind::JmxDungeon),
+    (b"JMXVEFF 0010", FileKind::JmxvEff),
+    (b"JMXVEFF 0011", FileKind::JmxvEff),
+    (b"JMXVEFF 0012", FileKind::JmxvEff),
+    (b"JMXVEFF 0013", FileKind::JmxvEff),
+    (b"JMXVENVI1003", FileKind::JmxEnvironment),
+    (b"JMXVMAPM1000", FileKind::JmxMapMesh),
+    (b"JMXVMAPO1001", FileKind::JmxMapObject),
+    (b"JMXVMAPT 1001", FileKind::JmxMapTexture),
+    (b"JMXVMFO 1000", FileKind::JmxMapInfo),
+    (b"JMXVNVM 1000", FileKind::JmxNvm),
+    (b"JMXVOBJI1000", FileKind::ObjectIfo),
+    (b"JMXV2DTI1001", FileKind::TileInfo2D),
+];
+
+/// Detects the format of a file by its magic.
+///
+/// Returns `None` for files without a `JMXV` magic and for versions none of the parsers support.
+pub fn detect(i: &[u8]) -> Option<FileKind> {
+    let kind = MAGICS
+        .iter()
+        .find(|(magic, _)| i.starts_with(magic))
+        .map(|&(_, kind)| kind)?;
+    match kind {
+        FileKind::ObjectIfo if is_object_string_ifo(i) => Some(FileKind::ObjectStringIfo),
+        kind => Some(kind),
+    }
+}
+
+/// objectstring.ifo shares its magic with object.ifo, but its entries start with a hex index.
+fn is_object_string_ifo(i: &[u8]) -> bool {
+    i.split(|&b| b == b'\n')
+        .nth(2)
+        .is_some_and(|entry| entry.starts_with(b"0x"))
+}
+
+/// Any file that can be recognized by [`detect`].
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum AnyFile {
+    JmxAnimation(JmxAnimation),
+    JmxBMesh(JmxBMesh),
+    JmxMat(JmxMat),
+    JmxSkeleton(JmxSkeleton),
+    JmxRes(JmxRes),
+    JmxCompound(JmxCompound),
+    JmxTexture(JmxTexture),
+    JmxDungeon(JmxDungeon),
+    JmxvEff(JmxvEff),
+    JmxEnvironment(JmxEnvironment),
+    JmxMapMesh(JmxMapMesh),
+    JmxMapObject(JmxMapObject),
+    JmxMapTexture(JmxMapTexture),
+    JmxMapInfo(JmxMapInfo),
+    JmxNvm(JmxNvm),
+    ObjectIfo(Vec<ObjectIfo>),
+    ObjectStringIfo(Vec<ObjectStringIfo>),
+    TileInfo2D(Vec<TileInfo2D>),
+}
+
+impl AnyFile {
+    pub fn kind(&self) -> FileKind {
+        match self {
+            AnyFile::JmxAnimation(_) => FileKind::JmxAnimation,
+            AnyFile::JmxBMesh(_) => FileKind::JmxBMesh,
+            AnyFile::JmxMat(_) => FileKind::JmxMat,
+            AnyFile::JmxSkeleton(_) => FileKind::JmxSkeleton,
+            AnyFile::JmxRes(_) => FileKind::JmxRes,
+            AnyFile::JmxCompound(_) => FileKind::JmxCompound,
+            AnyFile::JmxTexture(_) => FileKind::JmxTexture,
+            AnyFile::JmxDungeon(_) => FileKind::JmxDungeon,
+            AnyFile::JmxvEff(_) => FileKind::JmxvEff,
+            AnyFile::JmxEnvironment(_) => FileKind::JmxEnvironment,
+            AnyFile::JmxMapMesh(_) => FileKind::JmxMapMesh,
+            AnyFile::JmxMapObject(_) => FileKind::JmxMapObject,
+            AnyFile::JmxMapTexture(_) => FileKind::JmxMapTexture,
+            AnyFile::JmxMapInfo(_) => FileKind::JmxMapInfo,
+            AnyFile::JmxNvm(_) => FileKind::JmxNvm,
+            AnyFile::ObjectIfo(_) => FileKind::ObjectIfo,
+            AnyFile::ObjectStringIfo(_) => FileKind::ObjectStringIfo,
+            AnyFile::TileInfo2D(_) => FileKind::TileInfo2D,
+        }
+    }
+}
+
+/// Detects the format of a file by its magic and parses it accordingly.
+///
+/// The text formats are decoded as EUC_KR first, so the offsets of their errors are relative to
+/// the decoded text.
+pub fn parse_any(i: &[u8]) -> Result<AnyFile> {
+    let kind = detect(i).ok_or_else(|| Error::new("AnyFile", 0, ErrorKind::UnknownFormat))?;
+    let text = || encoding_rs::EUC_KR.decode_without_bom_handling(i).0;
+    match kind {
+        FileKind::JmxAnimation => JmxAnimation::parse(i).map(AnyFile::JmxAnimation),
+        FileKind::JmxBMesh => JmxBMesh::parse(i).map(AnyFile::JmxBMesh),
+        FileKind::JmxMat => JmxMat::parse(i).map(AnyFile::JmxMat),
+        FileKind::JmxSkeleton => JmxSkeleton::parse(i).map(AnyFile::JmxSkeleton),
+        FileKind::JmxRes => JmxRes::parse(i).map(AnyFile::JmxRes),
+        FileKind::JmxCompound => JmxCompound::parse(i).map(AnyFile::JmxCompound),
+        FileKind::JmxTexture => JmxTexture::parse(i).map(AnyFile::JmxTexture),
+        FileKind::JmxDungeon => JmxDungeon::parse(i).map(AnyFile::JmxDungeon),
+        FileKind::JmxvEff => JmxvEff::parse(i).map(AnyFile::JmxvEff),
+        FileKind::JmxEnvironment => JmxEnvironment::parse(i).map(AnyFile::JmxEnvironment),
+        FileKind::JmxMapMesh => JmxMapMesh::parse(i).map(AnyFile::JmxMapMesh),
+        FileKind::JmxMapObject => JmxMapObject::parse(i).map(AnyFile::JmxMapObject),
+        FileKind::JmxMapTexture => JmxMapTexture::parse(i).map(AnyFile::JmxMapTexture),
+        FileKind::JmxMapInfo => JmxMapInfo::parse(i).map(AnyFile::JmxMapInfo),
+        FileKind::JmxNvm => JmxNvm::parse(i).map(AnyFile::JmxNvm),
+        FileKind::ObjectIfo => ObjectIfo::parse(&text()).map(AnyFile::ObjectIfo),
+        FileKind::ObjectStringIfo => ObjectStringIfo::parse(&text()).map(AnyFile::ObjectStringIfo),
+        FileKind::TileInfo2D => TileInfo2D::parse(&text()).map(AnyFile::TileInfo2D),
+    }
+}
+
+#[test]
+fn detect_magic() {
+    assert_eq!(detect(b"JMXVBMS 0110\0\0\0\0"), Some(FileKind::JmxBMesh));
+    assert_eq!(detect(b"JMXVBMS 0200"), None);
+    assert_eq!(
+        detect(b"JMXVOBJI1000\n00001\n00000 0x00000000 \"res\\a.bsr\"\n"),
+        Some(FileKind::ObjectIfo)
+    );
+    assert_eq!(
+        detect(b"JMXVOBJI1000\n00001\n0x00000000 0x00000000 0 0 "),
+        Some(FileKind::ObjectStringIfo)
+    );
+    assert_eq!(
+        parse_any(b"DDS ").unwrap_err().kind(),
+        &ErrorKind::UnknownFormat
+    );
+}
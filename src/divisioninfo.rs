@@ -1,29 +1,37 @@
 use nom::bytes::complete::tag;
 use nom::combinator::{map, map_res};
 use nom::number::complete::le_u8;
-use nom::sequence::{pair, terminated};
-use nom::IResult;
+use nom::sequence::terminated;
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
 use std::net::Ipv4Addr;
 
+use crate::error::{finish, IResult, Result};
 use crate::parser_ext::multi::parse_objects_u8;
 use crate::parser_ext::string::sized_string;
-use crate::ttr_closure;
+use crate::span::context;
+use crate::{fields, ttr_closure};
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct DivisionInfo {
     pub content_id: u8,
     pub divisions: Box<[Division]>,
 }
 
 impl DivisionInfo {
-    pub fn parse<'i>(i: &'i [u8]) -> IResult<&'i [u8], Self> {
+    pub fn parse(i: &[u8]) -> Result<Self> {
+        finish("DivisionInfo", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Self> {
         map(
-            pair(le_u8, parse_objects_u8(Division::parse)),
+            fields!(
+                content_id: le_u8,
+                divisions: parse_objects_u8(Division::parse),
+            ),
             ttr_closure! {
                 DivisionInfo {
                     content_id, divisions
@@ -34,39 +42,45 @@ impl DivisionInfo {
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Division {
     pub name: Box<str>,
     pub gateways: Box<[Gateway]>,
 }
 
 impl Division {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            pair(
-                terminated(sized_string, tag(b"\x00")),
-                parse_objects_u8(Gateway::parse),
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "Division",
+            map(
+                fields!(
+                    name: terminated(sized_string, tag(b"\x00")),
+                    gateways: parse_objects_u8(Gateway::parse),
+                ),
+                ttr_closure! {
+                    Division {
+                        name, gateways
+                    }
+                },
             ),
-            ttr_closure! {
-                Division {
-                    name, gateways
-                }
-            },
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Gateway {
-    ip: Ipv4Addr,
+    pub ip: Ipv4Addr,
 }
 
 impl Gateway {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            terminated(map_res(sized_string, |addr| addr.parse()), tag(b"\x00")),
-            ttr_closure! { Gateway { ip } },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "Gateway",
+            map(
+                terminated(map_res(sized_string, |addr| addr.parse()), tag(b"\x00")),
+                ttr_closure! { Gateway { ip } },
+            ),
         )(i)
     }
 }
@@ -0,0 +1,198 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use nom::error::{ContextError, FromExternalError};
+use nom::Offset;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The error returned when a file could not be parsed.
+///
+/// Its [`Display`](fmt::Display) implementation renders the format, the chain of structs and fields
+/// that were being parsed and the absolute byte offset of the failure, e.g.
+/// `JmxBMesh > NavMesh > outlines[12] at 0x3F20: unexpected EOF`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    format: &'static str,
+    offset: usize,
+    context: Box<[Cow<'static, str>]>,
+    kind: ErrorKind,
+}
+
+impl Error {
+    pub(crate) fn new(format: &'static str, offset: usize, kind: ErrorKind) -> Self {
+        Error {
+            format,
+            offset,
+            context: Box::new([]),
+            kind,
+        }
+    }
+
+    /// The name of the format that was being parsed.
+    pub fn format(&self) -> &'static str {
+        self.format
+    }
+
+    /// The absolute byte offset into the input at which parsing failed.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The structs and fields that were being parsed, outermost first.
+    ///
+    /// Element indices of collections are their own entries in the form of `[index]`.
+    pub fn context(&self) -> &[Cow<'static, str>] {
+        &self.context
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
+impl std::error::Error for Error {}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.format)?;
+        for ctx in self.context.iter() {
+            if ctx.starts_with('[') {
+                f.write_str(ctx)?;
+            } else {
+                write!(f, " > {}", ctx)?;
+            }
+        }
+        write!(f, " at {:#X}: {}", self.offset, self.kind)
+    }
+}
+
+/// The reason a parse failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The input ended before the parser was done.
+    UnexpectedEof,
+    /// A magic tag or literal did not match.
+    Tag,
+    /// A value was read that is not valid at its position.
+    InvalidValue,
+    /// A section offset read from a header points past the end of the input.
+    InvalidOffset(u32),
+    /// The input does not start with the magic of any supported format.
+    UnknownFormat,
+    /// A value was rejected for the given reason.
+    External(Box<str>),
+    /// Any other parser failure.
+    Other(Box<str>),
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::UnexpectedEof => f.write_str("unexpected EOF"),
+            ErrorKind::Tag => f.write_str("unexpected tag"),
+            ErrorKind::InvalidValue => f.write_str("invalid value"),
+            ErrorKind::InvalidOffset(offset) => {
+                write!(f, "section offset {:#X} is out of bounds", offset)
+            },
+            ErrorKind::UnknownFormat => f.write_str("unknown file format"),
+            ErrorKind::External(reason) => f.write_str(reason),
+            ErrorKind::Other(description) => f.write_str(description),
+        }
+    }
+}
+
+impl From<nom::error::ErrorKind> for ErrorKind {
+    fn from(kind: nom::error::ErrorKind) -> Self {
+        use nom::error::ErrorKind as NomKind;
+        match kind {
+            NomKind::Eof | NomKind::Complete => ErrorKind::UnexpectedEof,
+            NomKind::Tag => ErrorKind::Tag,
+            NomKind::MapOpt | NomKind::MapRes | NomKind::Verify => ErrorKind::InvalidValue,
+            kind => ErrorKind::Other(kind.description().into()),
+        }
+    }
+}
+
+pub(crate) type IResult<I, O, E = ParseError<I>> = nom::IResult<I, O, E>;
+
+/// The error type threaded through the nom parsers of this crate.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ParseError<I> {
+    input: I,
+    kind: ErrorKind,
+    /// Innermost context first, as it is collected while unwinding.
+    context: Vec<Cow<'static, str>>,
+}
+
+impl<I> ParseError<I> {
+    pub(crate) fn new(input: I, kind: ErrorKind) -> Self {
+        ParseError {
+            input,
+            kind,
+            context: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with_context(mut self, ctx: impl Into<Cow<'static, str>>) -> Self {
+        self.context.push(ctx.into());
+        self
+    }
+}
+
+impl<I> nom::error::ParseError<I> for ParseError<I> {
+    fn from_error_kind(input: I, kind: nom::error::ErrorKind) -> Self {
+        ParseError::new(input, kind.into())
+    }
+
+    fn append(_: I, _: nom::error::ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<I> ContextError<I> for ParseError<I> {
+    fn add_context(_: I, ctx: &'static str, other: Self) -> Self {
+        other.with_context(ctx)
+    }
+}
+
+impl<I, E: fmt::Display> FromExternalError<I, E> for ParseError<I> {
+    fn from_external_error(input: I, _: nom::error::ErrorKind, e: E) -> Self {
+        ParseError::new(input, ErrorKind::External(e.to_string().into()))
+    }
+}
+
+/// Runs `parser` on the complete `input`, turning a failure into an [`Error`] for `format`.
+pub(crate) fn finish<I, O>(
+    format: &'static str,
+    input: I,
+    mut parser: impl FnMut(I) -> IResult<I, O>,
+) -> Result<O>
+where
+    I: Clone + Offset + nom::InputLength,
+{
+    match parser(input.clone()) {
+        Ok((_, o)) => Ok(o),
+        Err(nom::Err::Error(e) | nom::Err::Failure(e)) => Err(Error {
+            format,
+            offset: input.offset(&e.input),
+            context: e.context.into_iter().rev().collect(),
+            kind: e.kind,
+        }),
+        Err(nom::Err::Incomplete(_)) => Err(Error::new(
+            format,
+            input.input_len(),
+            ErrorKind::UnexpectedEof,
+        )),
+    }
+}
+
+#[test]
+fn error_display() {
+    let err = crate::jmxvbmt::JmxMat::parse(b"JMXVBMT 0102\x02\0\0\0\x04\0\0\0").unwrap_err();
+    assert_eq!(err.offset(), 0x14);
+    assert_eq!(
+        err.to_string(),
+        "JmxMat > materials[0] > Material at 0x14: unexpected EOF"
+    );
+}
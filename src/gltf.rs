@@ -0,0 +1,1381 @@
+//! Export of resources to binary glTF 2.0 (`.glb`).
+//!
+//! The client uses a left-handed coordinate system, glTF a right-handed one, so everything is
+//! mirrored along the z axis. The mirroring also turns the clockwise faces of the client into the
+//! counter-clockwise ones glTF expects.
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use mint::{ColumnMatrix4, Vector3, Vector4};
+use serde_json::{json, Value};
+
+use crate::dds::RgbaImage;
+use crate::jmxv2dti::TileInfo2D;
+use crate::jmxvban::JmxAnimation;
+use crate::jmxvbms::JmxBMesh;
+use crate::jmxvbmt::JmxMat;
+use crate::jmxvbsk::JmxSkeleton;
+use crate::jmxvbsr::JmxRes;
+use crate::jmxvddj::JmxTexture;
+use crate::jmxvmapm::{JmxMapMesh, TerrainMesh};
+use crate::jmxvmapo::JmxMapObject;
+use crate::jmxvobji::ObjectIfo;
+use crate::loader::{load, LoadError};
+use crate::math::{quat_normalize, Quat, Transform, Vec3};
+use crate::placement::{ObjectResolver, ObjectResource};
+use crate::region::RegionId;
+
+/// Resolves `file` relative to the directory of `base`, both using the client's `\` separators.
+pub(crate) fn sibling_path(base: &Path, file: &Path) -> PathBuf {
+    let base = base.to_string_lossy();
+    match base.rfind(['\\', '/']) {
+        Some(idx) => PathBuf::from(format!("{}\\{}", &base[..idx], file.display())),
+        None => file.to_path_buf(),
+    }
+}
+
+pub(crate) fn position(v: Vector3<f32>) -> Vec3 {
+    [v.x, v.y, -v.z]
+}
+
+pub(crate) fn rotation(q: Vector4<f32>) -> Quat {
+    quat_normalize([-q.x, -q.y, q.z, q.w])
+}
+
+/// Converts a transform between bone spaces the same way as [`position`] and [`rotation`].
+fn mirror(t: Transform) -> Transform {
+    let [x, y, z, w] = t.rotation;
+    let [tx, ty, tz] = t.translation;
+    Transform {
+        rotation: [-x, -y, z, w],
+        translation: [tx, ty, -tz],
+    }
+}
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const UNSIGNED_SHORT: u32 = 5123;
+const FLOAT: u32 = 5126;
+
+/// Collects the json and binary buffer of a glTF asset.
+pub(crate) struct GltfBuilder {
+    buffer: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    images: Vec<Value>,
+    textures: Vec<Value>,
+    pub(crate) materials: Vec<Value>,
+    pub(crate) meshes: Vec<Value>,
+    pub(crate) nodes: Vec<Value>,
+    pub(crate) skins: Vec<Value>,
+    pub(crate) animations: Vec<Value>,
+}
+
+impl GltfBuilder {
+    pub(crate) fn new() -> Self {
+        GltfBuilder {
+            buffer: Vec::new(),
+            buffer_views: Vec::new(),
+            accessors: Vec::new(),
+            images: Vec::new(),
+            textures: Vec::new(),
+            materials: Vec::new(),
+            meshes: Vec::new(),
+            nodes: Vec::new(),
+            skins: Vec::new(),
+            animations: Vec::new(),
+        }
+    }
+
+    pub(crate) fn push_node(&mut self, node: Value) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    /// Appends `child` to the children of `parent`.
+    pub(crate) fn add_child(&mut self, parent: usize, child: usize) {
+        let node = &mut self.nodes[parent];
+        match node.get_mut("children").and_then(Value::as_array_mut) {
+            Some(children) => children.push(child.into()),
+            None => node["children"] = json!([child]),
+        }
+    }
+
+    fn push_view(&mut self, data: &[u8], target: Option<u32>) -> usize {
+        // accessors must be aligned to their component size
+        self.buffer.resize(self.buffer.len().next_multiple_of(4), 0);
+        let mut view = json!({
+            "buffer": 0,
+            "byteOffset": self.buffer.len(),
+            "byteLength": data.len(),
+        });
+        if let Some(target) = target {
+            view["target"] = target.into();
+        }
+        self.buffer.extend_from_slice(data);
+        self.buffer_views.push(view);
+        self.buffer_views.len() - 1
+    }
+
+    fn push_accessor(&mut self, accessor: Value) -> usize {
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    /// Pushes an accessor of `N` floats per element, including its bounds.
+    pub(crate) fn push_f32<const N: usize>(
+        &mut self,
+        data: &[[f32; N]],
+        target: Option<u32>,
+    ) -> usize {
+        let bytes = data
+            .iter()
+            .flatten()
+            .flat_map(|f| f.to_le_bytes())
+            .collect::<Vec<_>>();
+        let (mut min, mut max) = ([f32::INFINITY; N], [f32::NEG_INFINITY; N]);
+        for element in data {
+            for c in 0..N {
+                min[c] = min[c].min(element[c]);
+                max[c] = max[c].max(element[c]);
+            }
+        }
+        let view = self.push_view(&bytes, target);
+        self.push_accessor(json!({
+            "bufferView": view,
+            "componentType": FLOAT,
+            "count": data.len(),
+            "type": accessor_type(N),
+            "min": &min[..],
+            "max": &max[..],
+        }))
+    }
+
+    /// Pushes an accessor of `N` unsigned shorts per element.
+    pub(crate) fn push_u16<const N: usize>(
+        &mut self,
+        data: &[[u16; N]],
+        target: Option<u32>,
+    ) -> usize {
+        let bytes = data
+            .iter()
+            .flatten()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+        let view = self.push_view(&bytes, target);
+        self.push_accessor(json!({
+            "bufferView": view,
+            "componentType": UNSIGNED_SHORT,
+            "count": data.len(),
+            "type": accessor_type(N),
+        }))
+    }
+
+    pub(crate) fn push_indices(&mut self, indices: &[u16]) -> usize {
+        let bytes = indices
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+        let view = self.push_view(&bytes, Some(ELEMENT_ARRAY_BUFFER));
+        self.push_accessor(json!({
+            "bufferView": view,
+            "componentType": UNSIGNED_SHORT,
+            "count": indices.len(),
+            "type": "SCALAR",
+        }))
+    }
+
+    pub(crate) fn push_matrices(&mut self, matrices: &[[f32; 16]]) -> usize {
+        let bytes = matrices
+            .iter()
+            .flatten()
+            .flat_map(|f| f.to_le_bytes())
+            .collect::<Vec<_>>();
+        let view = self.push_view(&bytes, None);
+        self.push_accessor(json!({
+            "bufferView": view,
+            "componentType": FLOAT,
+            "count": matrices.len(),
+            "type": "MAT4",
+        }))
+    }
+
+    /// Embeds `image` as a png and returns the index of the texture sampling it, or `None` if it
+    /// can't be encoded, like an empty image.
+    pub(crate) fn push_texture(&mut self, image: &RgbaImage) -> Option<usize> {
+        let view = self.push_view(&image.to_png().ok()?, None);
+        self.images
+            .push(json!({ "bufferView": view, "mimeType": "image/png" }));
+        self.textures
+            .push(json!({ "source": self.images.len() - 1 }));
+        Some(self.textures.len() - 1)
+    }
+
+    /// Pushes the vertex attributes and faces of `mesh` as a primitive, skipping faces that
+    /// reference missing vertices. Returns `None` if nothing is left to draw.
+    pub(crate) fn push_primitive(
+        &mut self,
+        mesh: &JmxBMesh,
+        material: Option<usize>,
+        skin_joints: Option<&HashMap<&str, u16>>,
+    ) -> Option<Value> {
+        let vertex_count = mesh.vertices.len();
+        let indices = mesh
+            .faces
+            .iter()
+            .filter(|face| face.0.iter().all(|&idx| (idx as usize) < vertex_count))
+            .flat_map(|face| face.0)
+            .collect::<Vec<_>>();
+        if indices.is_empty() {
+            return None;
+        }
+        let positions = mesh
+            .vertices
+            .iter()
+            .map(|v| position(v.position))
+            .collect::<Vec<_>>();
+        let normals = mesh
+            .vertices
+            .iter()
+            .map(|v| position(v.normal))
+            .collect::<Vec<_>>();
+        let uvs = mesh
+            .vertices
+            .iter()
+            .map(|v| [v.uv0.x, v.uv0.y])
+            .collect::<Vec<_>>();
+        let mut attributes = json!({
+            "POSITION": self.push_f32(&positions, Some(ARRAY_BUFFER)),
+            "NORMAL": self.push_f32(&normals, Some(ARRAY_BUFFER)),
+            "TEXCOORD_0": self.push_f32(&uvs, Some(ARRAY_BUFFER)),
+        });
+        if let (Some(joints), Some((bone_names, bone_indices))) = (skin_joints, &mesh.bone_data) {
+            let (joints, weights) = skin_weights(joints, bone_names, bone_indices, vertex_count);
+            attributes["JOINTS_0"] = self.push_u16(&joints, Some(ARRAY_BUFFER)).into();
+            attributes["WEIGHTS_0"] = self.push_f32(&weights, Some(ARRAY_BUFFER)).into();
+        }
+        let mut primitive = json!({
+            "attributes": attributes,
+            "indices": self.push_indices(&indices),
+        });
+        if let Some(material) = material {
+            primitive["material"] = material.into();
+        }
+        Some(primitive)
+    }
+
+    /// Serializes the asset into a `.glb` with a single scene made of `scene_nodes`.
+    pub(crate) fn finish(mut self, scene_nodes: &[usize]) -> Vec<u8> {
+        let mut root = json!({
+            "asset": { "version": "2.0", "generator": "sr_formats" },
+            "scene": 0,
+            "scenes": [{ "nodes": scene_nodes }],
+        });
+        // glTF doesn't allow empty arrays at the top level
+        for (key, values) in [
+            ("bufferViews", self.buffer_views),
+            ("accessors", self.accessors),
+            ("images", self.images),
+            ("textures", self.textures),
+            ("materials", self.materials),
+            ("meshes", self.meshes),
+            ("nodes", self.nodes),
+            ("skins", self.skins),
+            ("animations", self.animations),
+        ] {
+            if !values.is_empty() {
+                root[key] = Value::Array(values);
+            }
+        }
+        self.buffer.resize(self.buffer.len().next_multiple_of(4), 0);
+        if !self.buffer.is_empty() {
+            root["buffers"] = json!([{ "byteLength": self.buffer.len() }]);
+        }
+
+        let mut json = root.to_string().into_bytes();
+        json.resize(json.len().next_multiple_of(4), b' ');
+        let mut glb = Vec::with_capacity(28 + json.len() + self.buffer.len());
+        glb.extend_from_slice(b"glTF");
+        glb.extend_from_slice(&2u32.to_le_bytes());
+        let bin_len = if self.buffer.is_empty() {
+            0
+        } else {
+            8 + self.buffer.len()
+        };
+        glb.extend_from_slice(&((20 + json.len() + bin_len) as u32).to_le_bytes());
+        glb.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        glb.extend_from_slice(b"JSON");
+        glb.extend_from_slice(&json);
+        if !self.buffer.is_empty() {
+            glb.extend_from_slice(&(self.buffer.len() as u32).to_le_bytes());
+            glb.extend_from_slice(b"BIN\0");
+            glb.extend_from_slice(&self.buffer);
+        }
+        glb
+    }
+}
+
+fn accessor_type(components: usize) -> &'static str {
+    match components {
+        1 => "SCALAR",
+        2 => "VEC2",
+        3 => "VEC3",
+        4 => "VEC4",
+        _ => "MAT4",
+    }
+}
+
+/// Maps the per-mesh bone indices to skin joints and normalizes their weights.
+///
+/// Vertices without any known bone are bound to the first joint.
+fn skin_weights(
+    joints: &HashMap<&str, u16>,
+    bone_names: &[Box<str>],
+    bone_indices: &[crate::jmxvbms::BoneIndexData],
+    vertex_count: usize,
+) -> (Vec<[u16; 4]>, Vec<[f32; 4]>) {
+    let joint = |index: u8| {
+        bone_names
+            .get(index as usize)
+            .and_then(|name| joints.get(&**name).copied())
+    };
+    (0..vertex_count)
+        .map(|v| {
+            let Some(data) = bone_indices.get(v) else {
+                return ([0; 4], [1.0, 0.0, 0.0, 0.0]);
+            };
+            let influences = [
+                (joint(data.index0), f32::from(data.weight0)),
+                (joint(data.index1), f32::from(data.weight1)),
+            ];
+            let total: f32 = influences
+                .iter()
+                .filter(|(joint, _)| joint.is_some())
+                .map(|(_, weight)| weight)
+                .sum();
+            match influences {
+                [(Some(j0), w0), (Some(j1), w1)] if total > 0.0 => {
+                    ([j0, j1, 0, 0], [w0 / total, w1 / total, 0.0, 0.0])
+                },
+                [(Some(j), _), _] | [_, (Some(j), _)] => ([j, 0, 0, 0], [1.0, 0.0, 0.0, 0.0]),
+                _ => ([0; 4], [1.0, 0.0, 0.0, 0.0]),
+            }
+        })
+        .unzip()
+}
+
+/// The nodes created for the bones of a skeleton.
+pub(crate) struct SkeletonNodes<'s> {
+    /// The node of every bone by name.
+    pub(crate) nodes: HashMap<&'s str, usize>,
+    /// The joint index of every bone in the skin by name.
+    pub(crate) joints: HashMap<&'s str, u16>,
+    pub(crate) skin: usize,
+}
+
+/// Adds a node per bone below `parent` and a skin binding them.
+pub(crate) fn push_skeleton<'s>(
+    gltf: &mut GltfBuilder,
+    skeleton: &'s JmxSkeleton,
+    parent: usize,
+) -> SkeletonNodes<'s> {
+    let parents = skeleton.parent_indices();
+
+    let mut nodes = HashMap::new();
+    let mut joint_nodes = Vec::with_capacity(skeleton.bones.len());
+    for bone in skeleton.bones.iter() {
+        let node = gltf.push_node(json!({
+            "name": &*bone.name,
+            "rotation": rotation(bone.rotation_to_parent),
+            "translation": position(bone.translation_to_parent),
+        }));
+        nodes.insert(&*bone.name, node);
+        joint_nodes.push(node);
+    }
+    for (idx, parent_idx) in parents.iter().enumerate() {
+        match *parent_idx {
+            Some(parent_idx) => gltf.add_child(joint_nodes[parent_idx], joint_nodes[idx]),
+            None => gltf.add_child(parent, joint_nodes[idx]),
+        }
+    }
+
+    let inverse_binds = skeleton
+        .world_transforms()
+        .iter()
+        .map(|world| mirror(world.inverse()).to_matrix())
+        .collect::<Vec<_>>();
+    let inverse_bind_matrices = gltf.push_matrices(&inverse_binds);
+    gltf.skins.push(json!({
+        "joints": joint_nodes,
+        "inverseBindMatrices": inverse_bind_matrices,
+    }));
+
+    let joints = skeleton
+        .bones
+        .iter()
+        .enumerate()
+        .map(|(idx, bone)| (&*bone.name, idx as u16))
+        .collect();
+    SkeletonNodes {
+        nodes,
+        joints,
+        skin: gltf.skins.len() - 1,
+    }
+}
+
+/// Adds `animation` targeting the bone `nodes`, converting its key frame times to seconds.
+///
+/// Key frames whose time doesn't exceed the one before are left out.
+pub(crate) fn push_animation(
+    gltf: &mut GltfBuilder,
+    animation: &JmxAnimation,
+    nodes: &HashMap<&str, usize>,
+) {
+    // glTF requires strictly increasing sampler inputs, so drop the key frames that don't advance
+    let mut kept = Vec::<usize>::with_capacity(animation.key_frame_times.len());
+    for (idx, &ms) in animation.key_frame_times.iter().enumerate() {
+        if kept
+            .last()
+            .is_none_or(|&last| ms > animation.key_frame_times[last])
+        {
+            kept.push(idx);
+        }
+    }
+    let times = kept
+        .iter()
+        .map(|&idx| [animation.key_frame_times[idx] as f32 / 1000.0])
+        .collect::<Vec<_>>();
+    let mut shared_input = None;
+    let mut samplers = Vec::new();
+    let mut channels = Vec::new();
+    for bone in animation.animated_bones.iter() {
+        let Some(&node) = nodes.get(&*bone.name) else {
+            continue;
+        };
+        let frames = kept
+            .iter()
+            .take_while(|&&idx| idx < bone.keyframes.len())
+            .count();
+        if frames == 0 {
+            continue;
+        }
+        let input = match shared_input {
+            Some(input) if frames == times.len() => input,
+            _ if frames == times.len() => *shared_input.insert(gltf.push_f32(&times, None)),
+            _ => gltf.push_f32(&times[..frames], None),
+        };
+        let keyframes = kept[..frames]
+            .iter()
+            .map(|&idx| &bone.keyframes[idx])
+            .collect::<Vec<_>>();
+        let rotations = keyframes
+            .iter()
+            .map(|key| rotation(key.rotation))
+            .collect::<Vec<_>>();
+        let translations = keyframes
+            .iter()
+            .map(|key| position(key.translation))
+            .collect::<Vec<_>>();
+        for (path, output) in [
+            ("rotation", gltf.push_f32(&rotations, None)),
+            ("translation", gltf.push_f32(&translations, None)),
+        ] {
+            samplers.push(json!({ "input": input, "output": output, "interpolation": "LINEAR" }));
+            channels.push(json!({
+                "sampler": samplers.len() - 1,
+                "target": { "node": node, "path": path },
+            }));
+        }
+    }
+    if !channels.is_empty() {
+        gltf.animations.push(json!({
+            "name": &*animation.name,
+            "samplers": samplers,
+            "channels": channels,
+        }));
+    }
+}
+
+/// Creates glTF materials for the materials of a resource on demand.
+pub(crate) struct MaterialCache {
+    sets: Vec<(Box<Path>, JmxMat)>,
+    created: HashMap<Box<str>, Option<usize>>,
+}
+
+impl MaterialCache {
+    pub(crate) fn new(sets: Vec<(Box<Path>, JmxMat)>) -> Self {
+        MaterialCache {
+            sets,
+            created: HashMap::new(),
+        }
+    }
+
+    /// Returns the glTF material for the material called `name`, loading its diffuse map.
+    ///
+    /// A diffuse map that fails to load is added to `warnings` and leaves the material without a
+    /// texture.
+    pub(crate) fn get(
+        &mut self,
+        gltf: &mut GltfBuilder,
+        loader: &mut dyn FnMut(&Path) -> io::Result<Vec<u8>>,
+        name: &str,
+        warnings: &mut Vec<LoadError>,
+    ) -> Option<usize> {
+        if let Some(&material) = self.created.get(name) {
+            return material;
+        }
+        let found = self.sets.iter().find_map(|(path, set)| {
+            set.0
+                .iter()
+                .find(|material| &*material.name == name)
+                .map(|material| (path, material))
+        });
+        let material = match found {
+            Some((set_path, material)) => {
+                let diffuse = material.diffuse;
+                let base_color =
+                    [diffuse.x, diffuse.y, diffuse.z, diffuse.w].map(|c| c.clamp(0.0, 1.0));
+                let mut json = json!({
+                    "name": name,
+                    "pbrMetallicRoughness": {
+                        "baseColorFactor": base_color,
+                        "metallicFactor": 0.0,
+                        "roughnessFactor": 1.0,
+                    },
+                });
+                if !material.diffuse_map.as_os_str().is_empty() {
+                    let path = if material.absolute_diffuse_map_path {
+                        material.diffuse_map.to_path_buf()
+                    } else {
+                        sibling_path(set_path, &material.diffuse_map)
+                    };
+                    let levels = load(loader, &path, JmxTexture::parse).and_then(|texture| {
+                        texture.decode().map_err(|error| LoadError::Parse {
+                            path: path.into(),
+                            error,
+                        })
+                    });
+                    let levels = match levels {
+                        Ok(levels) => levels,
+                        Err(error) => {
+                            warnings.push(error);
+                            Box::default()
+                        },
+                    };
+                    if let Some(image) = levels.first() {
+                        if let Some(texture) = gltf.push_texture(image) {
+                            if image.data.chunks_exact(4).any(|pixel| pixel[3] < 255) {
+                                json["alphaMode"] = "MASK".into();
+                            }
+                            json["pbrMetallicRoughness"]["baseColorTexture"] =
+                                json!({ "index": texture });
+                        }
+                    }
+                }
+                gltf.materials.push(json);
+                Some(gltf.materials.len() - 1)
+            },
+            None => None,
+        };
+        self.created.insert(name.into(), material);
+        material
+    }
+}
+
+/// Adds the meshes of `res` with their materials, returning a node per mesh for the caller to
+/// place.
+///
+/// Material sets, meshes and textures that fail to load are added to `warnings` and left out.
+fn push_meshes(
+    gltf: &mut GltfBuilder,
+    res: &JmxRes,
+    loader: &mut dyn FnMut(&Path) -> io::Result<Vec<u8>>,
+    skeleton_nodes: Option<&SkeletonNodes>,
+    warnings: &mut Vec<LoadError>,
+) -> Vec<Value> {
+    let mut sets = Vec::with_capacity(res.material_sets.len());
+    for descriptor in res.material_sets.iter() {
+        match load(loader, &descriptor.path, JmxMat::parse) {
+            Ok(set) => sets.push((descriptor.path.clone(), set)),
+            Err(error) => warnings.push(error),
+        }
+    }
+    let mut materials = MaterialCache::new(sets);
+
+    let mut nodes = Vec::with_capacity(res.mesh_paths.len());
+    for (path, _) in res.mesh_paths.iter() {
+        let mesh = match load(loader, path, JmxBMesh::parse) {
+            Ok(mesh) => mesh,
+            Err(error) => {
+                warnings.push(error);
+                continue;
+            },
+        };
+        let material = materials.get(gltf, loader, &mesh.header.material, warnings);
+        let joints = skeleton_nodes.map(|skeleton| &skeleton.joints);
+        let Some(primitive) = gltf.push_primitive(&mesh, material, joints) else {
+            continue;
+        };
+        gltf.meshes.push(json!({
+            "name": &*mesh.header.name,
+            "primitives": [primitive],
+        }));
+        let mut node = json!({ "name": &*mesh.header.name, "mesh": gltf.meshes.len() - 1 });
+        if let (Some(skeleton), Some(_)) = (skeleton_nodes, &mesh.bone_data) {
+            node["skin"] = skeleton.skin.into();
+        }
+        nodes.push(node);
+    }
+    nodes
+}
+
+/// Exports a resource with its meshes, materials, skeleton and animations as a `.glb`.
+///
+/// `loader` is called with the paths as they are stored in the files, e.g.
+/// `res\char\...\foo.bms`, and has to return the contents of the file.
+pub fn export<F>(res: &JmxRes, mut loader: F) -> Result<Vec<u8>, LoadError>
+where
+    F: FnMut(&Path) -> io::Result<Vec<u8>>,
+{
+    let loader: &mut dyn FnMut(&Path) -> io::Result<Vec<u8>> = &mut loader;
+    let mut gltf = GltfBuilder::new();
+    let root = gltf.push_node(json!({ "name": &*res.header.name }));
+
+    let skeleton = match res.skeleton_paths.first() {
+        Some((path, _)) => Some(load(loader, path, JmxSkeleton::parse)?),
+        None => None,
+    };
+    let skeleton_nodes = skeleton
+        .as_ref()
+        .map(|skeleton| push_skeleton(&mut gltf, skeleton, root));
+
+    let mut failures = Vec::new();
+    let nodes = push_meshes(
+        &mut gltf,
+        res,
+        loader,
+        skeleton_nodes.as_ref(),
+        &mut failures,
+    );
+    if let Some(error) = failures.into_iter().next() {
+        return Err(error);
+    }
+    for node in nodes {
+        let node = gltf.push_node(node);
+        gltf.add_child(root, node);
+    }
+
+    if let Some(nodes) = &skeleton_nodes {
+        for path in res.animation.paths.iter() {
+            let animation = load(loader, path, JmxAnimation::parse)?;
+            push_animation(&mut gltf, &animation, &nodes.nodes);
+        }
+    }
+
+    Ok(gltf.finish(&[root]))
+}
+
+/// The settings of [`export_region`].
+#[derive(Copy, Clone, Debug)]
+pub struct RegionExportOptions {
+    /// The bits of [`MapMeshCell::texture`](crate::jmxvmapm::MapMeshCell::texture) holding the
+    /// index of the tile in `tile2d.ifo`.
+    ///
+    /// Defaults to `0x3FF`, assuming that the index takes the lower 10 bits and the bits above
+    /// it mean something else. Change it if tiles come out wrong.
+    pub tile_index_mask: u16,
+}
+
+impl Default for RegionExportOptions {
+    fn default() -> Self {
+        RegionExportOptions {
+            tile_index_mask: 0x3FF,
+        }
+    }
+}
+
+/// A region exported by [`export_region`].
+#[derive(Debug)]
+pub struct RegionExport {
+    /// The `.glb` file.
+    pub glb: Vec<u8>,
+    /// The files that couldn't be loaded. Cells whose tile texture failed and materials whose
+    /// diffuse map failed are exported without a texture, objects and meshes that failed are
+    /// left out.
+    pub warnings: Vec<LoadError>,
+}
+
+/// Adds the terrain as a mesh with a primitive per tile texture, returning its node.
+///
+/// Tile textures that fail to load are added to `warnings` and leave their primitive without a
+/// material.
+fn push_terrain(
+    gltf: &mut GltfBuilder,
+    terrain: &TerrainMesh,
+    tiles: &[TileInfo2D],
+    tile_index_mask: u16,
+    loader: &mut dyn FnMut(&Path) -> io::Result<Vec<u8>>,
+    warnings: &mut Vec<LoadError>,
+) -> usize {
+    let positions = terrain
+        .vertices
+        .iter()
+        .map(|v| position(v.position))
+        .collect::<Vec<_>>();
+    let normals = terrain
+        .vertices
+        .iter()
+        .map(|v| position(v.normal))
+        .collect::<Vec<_>>();
+    let uvs = terrain
+        .vertices
+        .iter()
+        .map(|v| [v.uv.x, v.uv.y])
+        .collect::<Vec<_>>();
+    let attributes = json!({
+        "POSITION": gltf.push_f32(&positions, Some(ARRAY_BUFFER)),
+        "NORMAL": gltf.push_f32(&normals, Some(ARRAY_BUFFER)),
+        "TEXCOORD_0": gltf.push_f32(&uvs, Some(ARRAY_BUFFER)),
+    });
+
+    // every cell is textured by the tile of its first vertex
+    let mut cells_by_tile = BTreeMap::<u16, Vec<u16>>::new();
+    for triangle in terrain.indices.chunks_exact(3) {
+        let tile = terrain.vertices[triangle[0] as usize].texture & tile_index_mask;
+        // mirroring flips the winding, so restore it by swapping two corners
+        cells_by_tile.entry(tile).or_default().extend([
+            triangle[0] as u16,
+            triangle[2] as u16,
+            triangle[1] as u16,
+        ]);
+    }
+    let mut primitives = Vec::with_capacity(cells_by_tile.len());
+    for (tile, indices) in cells_by_tile {
+        let mut primitive = json!({
+            "attributes": attributes,
+            "indices": gltf.push_indices(&indices),
+        });
+        if let Some(info) = tiles.iter().find(|info| info.index == tile) {
+            let path = PathBuf::from(format!("tile2d\\{}", info.file.display()));
+            let levels = load(loader, &path, JmxTexture::parse).and_then(|texture| {
+                texture.decode().map_err(|error| LoadError::Parse {
+                    path: path.into(),
+                    error,
+                })
+            });
+            let levels = match levels {
+                Ok(levels) => levels,
+                Err(error) => {
+                    warnings.push(error);
+                    Box::default()
+                },
+            };
+            if let Some(texture) = levels.first().and_then(|image| gltf.push_texture(image)) {
+                gltf.materials.push(json!({
+                    "name": &*info.file.to_string_lossy(),
+                    "pbrMetallicRoughness": {
+                        "baseColorTexture": { "index": texture },
+                        "metallicFactor": 0.0,
+                        "roughnessFactor": 1.0,
+                    },
+                }));
+                primitive["material"] = (gltf.materials.len() - 1).into();
+            }
+        }
+        primitives.push(primitive);
+    }
+    gltf.meshes
+        .push(json!({ "name": "terrain", "primitives": primitives }));
+    gltf.push_node(json!({ "name": "terrain", "mesh": gltf.meshes.len() - 1 }))
+}
+
+/// Mirrors a client transform matrix along the z axis.
+fn mirror_matrix(m: ColumnMatrix4<f32>) -> [f32; 16] {
+    let mut m: [f32; 16] = m.into();
+    for column in 0..4 {
+        for row in 0..4 {
+            if (column == 2) != (row == 2) {
+                m[column * 4 + row] = -m[column * 4 + row];
+            }
+        }
+    }
+    m
+}
+
+/// Exports a world region with its terrain and the objects placed in it as a `.glb`.
+///
+/// The terrain is textured with the tiles of `tiles`, which are loaded as `tile2d\<file>`.
+/// Tiles, objects, meshes, materials and textures that fail to load don't abort the export but
+/// are reported in [`RegionExport::warnings`].
+/// Fails with [`LoadError::MapMesh`] if `map_mesh` doesn't cover the whole region.
+/// The objects are resolved through `object_ifo` like [`ObjectResolver`] does and placed
+/// relative to the region's origin. Only their static meshes are exported, without skeletons or
+/// animations.
+///
+/// [`ObjectResolver`]: crate::placement::ObjectResolver
+pub fn export_region<F>(
+    region: RegionId,
+    map_mesh: &JmxMapMesh,
+    map_object: &JmxMapObject,
+    object_ifo: &[ObjectIfo],
+    tiles: &[TileInfo2D],
+    options: RegionExportOptions,
+    mut loader: F,
+) -> Result<RegionExport, LoadError>
+where
+    F: FnMut(&Path) -> io::Result<Vec<u8>>,
+{
+    let loader: &mut dyn FnMut(&Path) -> io::Result<Vec<u8>> = &mut loader;
+    let mut gltf = GltfBuilder::new();
+    let mut warnings = Vec::new();
+    let root = gltf.push_node(json!({ "name": format!("{:#06X}", region.0) }));
+    let terrain = map_mesh.terrain_mesh().map_err(LoadError::MapMesh)?;
+    let terrain = push_terrain(
+        &mut gltf,
+        &terrain,
+        tiles,
+        options.tile_index_mask,
+        loader,
+        &mut warnings,
+    );
+    gltf.add_child(root, terrain);
+
+    let placed = ObjectResolver::new(object_ifo, &mut *loader).resolve(map_object);
+    let zero = Vector3 {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    let origin = region.to_world(zero).unwrap_or(zero);
+    let mut res_nodes = HashMap::<Box<Path>, Vec<Value>>::new();
+    for object in placed {
+        let object = match object {
+            Ok(object) => object,
+            Err(error) => {
+                warnings.push(error);
+                continue;
+            },
+        };
+        let mut transform = object.transform;
+        transform.w.x -= origin.x;
+        transform.w.z -= origin.z;
+        let name = object.path.to_string_lossy().into_owned();
+        let node = gltf.push_node(json!({ "name": name, "matrix": mirror_matrix(transform) }));
+        gltf.add_child(root, node);
+
+        let paths = match &object.resource {
+            ObjectResource::Res(_) => vec![object.path.clone()],
+            ObjectResource::Compound(compound) => compound.resource_paths.to_vec(),
+        };
+        for path in paths {
+            if !res_nodes.contains_key(&path) {
+                let nodes = match &object.resource {
+                    ObjectResource::Res(res) => {
+                        push_meshes(&mut gltf, res, loader, None, &mut warnings)
+                    },
+                    ObjectResource::Compound(_) => match load(loader, &path, JmxRes::parse) {
+                        Ok(res) => push_meshes(&mut gltf, &res, loader, None, &mut warnings),
+                        Err(error) => {
+                            warnings.push(error);
+                            Vec::new()
+                        },
+                    },
+                };
+                res_nodes.insert(path.clone(), nodes);
+            }
+            for child in res_nodes[&path].clone() {
+                let child = gltf.push_node(child);
+                gltf.add_child(node, child);
+            }
+        }
+    }
+
+    Ok(RegionExport {
+        glb: gltf.finish(&[root]),
+        warnings,
+    })
+}
+
+#[test]
+fn export_static_mesh() {
+    use crate::jmxvbms::{Face, JmxBMeshHeader, NavFlags, Vertex, VertexFlags};
+    use crate::jmxvbsr::{Animation, CollisionInfo, JmxResHeader};
+    use mint::Vector2;
+
+    let vertex = |x, z| Vertex {
+        position: Vector3 { x, y: 0.0, z },
+        normal: Vector3 {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        },
+        uv0: Vector2 { x, y: z },
+        uv1: None,
+        float0: 0.0,
+        int0: 0,
+        int1: 0,
+    };
+    let mesh = JmxBMesh {
+        header: JmxBMeshHeader {
+            version: *b"0110",
+            vertex: 0,
+            skin: 0,
+            face: 0,
+            cloth_vertex: 0,
+            cloth_edge: 0,
+            bounding_box: 0,
+            gate: 0,
+            nav_mesh: 0,
+            unk0: 0,
+            unk1: 0,
+            unk3: 0,
+            nav_flags: NavFlags::empty(),
+            sub_prim_count: 0,
+            vertex_flags: VertexFlags::empty(),
+            unk4: 0,
+            name: "quad".into(),
+            material: "missing".into(),
+            unk5: 0,
+        },
+        vertices: Box::new([vertex(0.0, 0.0), vertex(1.0, 0.0), vertex(0.0, 1.0)]),
+        light_map_path: None,
+        bone_data: None,
+        // the second face references a vertex that doesn't exist
+        faces: Box::new([Face([0, 1, 2]), Face([0, 1, 3])]),
+        cloth_vertex: Box::new([]),
+        cloth_edges: None,
+        bounding_box: [0.0; 6],
+        gates: Box::new([]),
+        nav_mesh: None,
+        unread: Box::new([]),
+    };
+    let mut mesh_file = Vec::new();
+    mesh.write(&mut mesh_file).unwrap();
+
+    let res = JmxRes {
+        header: JmxResHeader {
+            version: *b"0109",
+            material_offset: 0,
+            mesh_offset: 0,
+            skeleton_offset: 0,
+            animation_offset: 0,
+            prim_mesh_group_offset: 0,
+            prim_ani_group_offset: 0,
+            mod_palette_offset: 0,
+            collision_offset: 0,
+            unk0: 0,
+            unk1: 0,
+            unk2: 0,
+            unk3: 0,
+            unk4: 0,
+            res_type: crate::ResourceType::Artifact,
+            name: "res".into(),
+            unk5: 0,
+            unk6: 0,
+        },
+        bounding_box: CollisionInfo {
+            collision_mesh: "".into(),
+            collision_box0: [0.0; 6],
+            collision_box1: [0.0; 6],
+            collision_matrix: None,
+        },
+        material_sets: Box::new([]),
+        mesh_paths: Box::new([(Path::new("res\\quad.bms").into(), None)]),
+        animation: Animation {
+            type_version: 0,
+            type_user_define: 0,
+            paths: Box::new([]),
+        },
+        skeleton_paths: Box::new([]),
+        mesh_groups: Box::new([]),
+        animation_groups: Box::new([]),
+        mod_palette: None,
+    };
+
+    let glb = export(&res, |path| match path.to_str() {
+        Some("res\\quad.bms") => Ok(mesh_file.clone()),
+        _ => Err(io::ErrorKind::NotFound.into()),
+    })
+    .unwrap();
+    assert_eq!(&glb[..4], b"glTF");
+    assert_eq!(
+        glb.len(),
+        u32::from_le_bytes(glb[8..12].try_into().unwrap()) as usize
+    );
+    let json_len = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+    let json: Value = serde_json::from_slice(&glb[20..20 + json_len]).unwrap();
+    let primitive = &json["meshes"][0]["primitives"][0];
+    assert_eq!(
+        json["accessors"][primitive["indices"].as_u64().unwrap() as usize]["count"],
+        3
+    );
+    assert_eq!(json["accessors"][0]["max"], json!([1.0, 0.0, 0.0]));
+    assert!(primitive.get("material").is_none());
+
+    let err = export(&res, |_| Err(io::ErrorKind::NotFound.into())).unwrap_err();
+    assert!(matches!(err, LoadError::Load { .. }));
+}
+
+#[test]
+fn export_skinned_mesh() {
+    use crate::jmxvbms::{BoneIndexData, Face, JmxBMeshHeader, NavFlags, Vertex, VertexFlags};
+    use crate::jmxvbsr::{Animation, CollisionInfo, JmxResHeader};
+    use mint::Vector2;
+
+    let vertex = |x, z| Vertex {
+        position: Vector3 { x, y: 0.0, z },
+        normal: Vector3 {
+            x: 0.0,
+            y: 1.0,
+            z: 0.0,
+        },
+        uv0: Vector2 { x, y: z },
+        uv1: None,
+        float0: 0.0,
+        int0: 0,
+        int1: 0,
+    };
+    let influence = |index0, weight0, index1, weight1| BoneIndexData {
+        index0,
+        weight0,
+        index1,
+        weight1,
+    };
+    let mesh = JmxBMesh {
+        header: JmxBMeshHeader {
+            version: *b"0110",
+            vertex: 0,
+            skin: 0,
+            face: 0,
+            cloth_vertex: 0,
+            cloth_edge: 0,
+            bounding_box: 0,
+            gate: 0,
+            nav_mesh: 0,
+            unk0: 0,
+            unk1: 0,
+            unk3: 0,
+            nav_flags: NavFlags::empty(),
+            sub_prim_count: 0,
+            vertex_flags: VertexFlags::empty(),
+            unk4: 0,
+            name: "arm".into(),
+            material: "missing".into(),
+            unk5: 0,
+        },
+        vertices: Box::new([vertex(0.0, 0.0), vertex(1.0, 0.0), vertex(0.0, 1.0)]),
+        light_map_path: None,
+        // the mesh names its bones in a different order than the skeleton
+        bone_data: Some((
+            Box::new(["child".into(), "root".into()]),
+            Box::new([
+                influence(0, 1000, 0xFF, 0),
+                influence(0, 500, 1, 500),
+                influence(1, 1000, 0xFF, 0),
+            ]),
+        )),
+        faces: Box::new([Face([0, 1, 2])]),
+        cloth_vertex: Box::new([]),
+        cloth_edges: None,
+        bounding_box: [0.0; 6],
+        gates: Box::new([]),
+        nav_mesh: None,
+        unread: Box::new([]),
+    };
+    let mut mesh_file = Vec::new();
+    mesh.write(&mut mesh_file).unwrap();
+
+    let string = |file: &mut Vec<u8>, s: &str| {
+        file.extend((s.len() as u32).to_le_bytes());
+        file.extend(s.as_bytes());
+    };
+    let floats = |file: &mut Vec<u8>, floats: &[f32]| {
+        file.extend(floats.iter().flat_map(|f| f.to_le_bytes()));
+    };
+    let mut skeleton_file = b"JMXVBSK 0101".to_vec();
+    skeleton_file.extend(2u32.to_le_bytes());
+    for (name, parent, x) in [("root", "", 0.0), ("child", "root", 1.0)] {
+        skeleton_file.push(0);
+        string(&mut skeleton_file, name);
+        string(&mut skeleton_file, parent);
+        for _ in 0..3 {
+            floats(&mut skeleton_file, &[0.0, 0.0, 0.0, 1.0, x, 0.0, 0.0]);
+        }
+        skeleton_file.extend(0u32.to_le_bytes());
+    }
+    skeleton_file.extend([0; 8]);
+
+    let mut animation_file = b"JMXVBAN 0102".to_vec();
+    animation_file.extend([0; 8]);
+    string(&mut animation_file, "wave");
+    // duration, frames per second and is_continuous
+    for v in [1000u32, 30, 0] {
+        animation_file.extend(v.to_le_bytes());
+    }
+    // the third key frame repeats the time of the second
+    for v in [4u32, 0, 500, 500, 1000] {
+        animation_file.extend(v.to_le_bytes());
+    }
+    animation_file.extend(1u32.to_le_bytes());
+    string(&mut animation_file, "child");
+    animation_file.extend(4u32.to_le_bytes());
+    for x in [0.0, 1.0, 2.0, 3.0] {
+        floats(&mut animation_file, &[0.0, 0.0, 0.0, 1.0, x, 0.0, 0.0]);
+    }
+
+    let res = JmxRes {
+        header: JmxResHeader {
+            version: *b"0109",
+            material_offset: 0,
+            mesh_offset: 0,
+            skeleton_offset: 0,
+            animation_offset: 0,
+            prim_mesh_group_offset: 0,
+            prim_ani_group_offset: 0,
+            mod_palette_offset: 0,
+            collision_offset: 0,
+            unk0: 0,
+            unk1: 0,
+            unk2: 0,
+            unk3: 0,
+            unk4: 0,
+            res_type: crate::ResourceType::Character,
+            name: "res".into(),
+            unk5: 0,
+            unk6: 0,
+        },
+        bounding_box: CollisionInfo {
+            collision_mesh: "".into(),
+            collision_box0: [0.0; 6],
+            collision_box1: [0.0; 6],
+            collision_matrix: None,
+        },
+        material_sets: Box::new([]),
+        mesh_paths: Box::new([(Path::new("res\\arm.bms").into(), None)]),
+        animation: Animation {
+            type_version: 0,
+            type_user_define: 0,
+            paths: Box::new([Path::new("res\\wave.ban").into()]),
+        },
+        skeleton_paths: Box::new([(Path::new("res\\arm.bsk").into(), Box::new([]))]),
+        mesh_groups: Box::new([]),
+        animation_groups: Box::new([]),
+        mod_palette: None,
+    };
+
+    let glb = export(&res, |path| match path.to_str() {
+        Some("res\\arm.bms") => Ok(mesh_file.clone()),
+        Some("res\\arm.bsk") => Ok(skeleton_file.clone()),
+        Some("res\\wave.ban") => Ok(animation_file.clone()),
+        _ => Err(io::ErrorKind::NotFound.into()),
+    })
+    .unwrap();
+    let json_len = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+    let json: Value = serde_json::from_slice(&glb[20..20 + json_len]).unwrap();
+    let bin = &glb[28 + json_len..];
+    let accessor_bytes = |accessor: &Value| {
+        let accessor = &json["accessors"][accessor.as_u64().unwrap() as usize];
+        let view = &json["bufferViews"][accessor["bufferView"].as_u64().unwrap() as usize];
+        let offset = view["byteOffset"].as_u64().unwrap() as usize;
+        &bin[offset..offset + view["byteLength"].as_u64().unwrap() as usize]
+    };
+    let u16s = |accessor: &Value| {
+        accessor_bytes(accessor)
+            .chunks_exact(2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+            .collect::<Vec<_>>()
+    };
+    let f32s = |accessor: &Value| {
+        accessor_bytes(accessor)
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect::<Vec<_>>()
+    };
+
+    // the joints are the skeleton's bone indices, the weights normalized
+    let attributes = &json["meshes"][0]["primitives"][0]["attributes"];
+    assert_eq!(
+        u16s(&attributes["JOINTS_0"]),
+        [1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0]
+    );
+    assert_eq!(
+        f32s(&attributes["WEIGHTS_0"]),
+        [1.0, 0.0, 0.0, 0.0, 0.5, 0.5, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]
+    );
+    let mesh_node = json["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|node| node["name"] == "arm")
+        .unwrap();
+    assert_eq!(mesh_node["skin"], 0);
+
+    let skin = &json["skins"][0];
+    let joints = skin["joints"].as_array().unwrap();
+    assert_eq!(
+        json["nodes"][joints[1].as_u64().unwrap() as usize]["name"],
+        "child"
+    );
+    let inverse_binds = f32s(&skin["inverseBindMatrices"]);
+    assert_eq!(inverse_binds.len(), 32);
+    // the child's inverse bind matrix moves it back to the origin
+    assert_eq!(inverse_binds[16 + 12..16 + 15], [-1.0, 0.0, 0.0]);
+
+    let animation = &json["animations"][0];
+    assert_eq!(animation["name"], "wave");
+    let sampler = &animation["samplers"][0];
+    assert_eq!(f32s(&sampler["input"]), [0.0, 0.5, 1.0]);
+    assert_eq!(animation["channels"][1]["target"]["path"], "translation");
+    assert_eq!(animation["channels"][1]["sampler"], 1);
+    assert_eq!(
+        f32s(&animation["samplers"][1]["output"]),
+        [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 3.0, 0.0, 0.0]
+    );
+}
+
+#[test]
+fn export_region_scene() {
+    use crate::dds::EncodeFormat;
+    use crate::jmxvmapm::{MapBlock, MapMeshCell};
+    use crate::jmxvmapo::{MapObject, MapObjectGroup};
+
+    let blocks = (0..36)
+        .map(|_| MapBlock {
+            name: "block".into(),
+            cells: (0..17 * 17)
+                .map(|_| MapMeshCell {
+                    height: 0.0,
+                    texture: 0x0401,
+                    brightness: 0xFF,
+                })
+                .collect(),
+            density: 0,
+            unk0: 0,
+            sea_level: 0.0,
+            extra_data: Box::new([]),
+            height_min: 0.0,
+            height_max: 0.0,
+            unk0_buffer: Box::new([]),
+        })
+        .collect();
+    let map_mesh = JmxMapMesh { blocks };
+    let region = RegionId::new(1, 2);
+    let placed = |id, unique_id| MapObject {
+        id,
+        position: Vector3 {
+            x: 10.0,
+            y: 0.0,
+            z: 20.0,
+        },
+        visibility_flag: 0,
+        theta: 0.0,
+        unique_id,
+        scale: 0,
+        region,
+    };
+    let map_object = JmxMapObject {
+        objects: Box::new([MapObjectGroup {
+            entries: Box::new([placed(0, 1)]),
+        }]),
+    };
+    let object_ifo = [
+        ObjectIfo {
+            index: 0,
+            flag: 0,
+            path: Path::new("res\\empty.cpd").into(),
+        },
+        ObjectIfo {
+            index: 1,
+            flag: 0,
+            path: Path::new("res\\missing.cpd").into(),
+        },
+    ];
+    let tiles = [TileInfo2D {
+        index: 1,
+        flag: 0,
+        category: "grass".into(),
+        file: Path::new("grass.ddj").into(),
+        extra: Vec::new(),
+    }];
+    let mut cpd = b"JMXVCPD 0101".to_vec();
+    for v in [56u32, 60, 0, 0, 0, 0, 0, 0x20002, 0, 0, 0, 0, 0] {
+        cpd.extend(v.to_le_bytes());
+    }
+    let image = RgbaImage {
+        width: 4,
+        height: 4,
+        data: vec![0x80; 64].into(),
+    };
+    let mut ddj = Vec::new();
+    JmxTexture::encode(&image, EncodeFormat::Dxt1, false, 3)
+        .write(&mut ddj)
+        .unwrap();
+
+    let export_objects = |map_object: &JmxMapObject, options, with_tiles: bool| {
+        export_region(
+            region,
+            &map_mesh,
+            map_object,
+            &object_ifo,
+            &tiles,
+            options,
+            |path| match path.to_str() {
+                Some("res\\empty.cpd") => Ok(cpd.clone()),
+                Some("tile2d\\grass.ddj") if with_tiles => Ok(ddj.clone()),
+                _ => Err(io::ErrorKind::NotFound.into()),
+            },
+        )
+        .unwrap()
+    };
+    let export = |options, with_tiles| export_objects(&map_object, options, with_tiles);
+    let parse_json = |glb: &[u8]| {
+        let json_len = u32::from_le_bytes(glb[12..16].try_into().unwrap()) as usize;
+        serde_json::from_slice::<Value>(&glb[20..20 + json_len]).unwrap()
+    };
+
+    let exported = export(RegionExportOptions::default(), true);
+    assert!(exported.warnings.is_empty());
+    let json = parse_json(&exported.glb);
+    let primitives = json["meshes"][0]["primitives"].as_array().unwrap();
+    assert_eq!(primitives.len(), 1);
+    assert_eq!(primitives[0]["material"], 0);
+    let object = &json["nodes"][json["nodes"][0]["children"][1].as_u64().unwrap() as usize];
+    assert_eq!(object["name"], "res\\empty.cpd");
+    assert_eq!(object["matrix"][12], 10.0);
+    assert_eq!(object["matrix"][14], -20.0);
+
+    // a missing tile texture leaves the terrain without a material instead of failing
+    let exported = export(RegionExportOptions::default(), false);
+    assert_eq!(exported.warnings.len(), 1);
+    assert!(matches!(exported.warnings[0], LoadError::Load { .. }));
+    let json = parse_json(&exported.glb);
+    assert!(json["meshes"][0]["primitives"][0].get("material").is_none());
+
+    // with all bits taken as the index, the cells refer to tile 0x401 which isn't listed
+    let options = RegionExportOptions {
+        tile_index_mask: 0xFFFF,
+    };
+    let exported = export(options, true);
+    assert!(exported.warnings.is_empty());
+    let json = parse_json(&exported.glb);
+    assert!(json["meshes"][0]["primitives"][0].get("material").is_none());
+
+    // an object whose resource is missing is left out, the others are still placed
+    let with_missing = JmxMapObject {
+        objects: Box::new([MapObjectGroup {
+            entries: Box::new([placed(1, 2), placed(0, 1)]),
+        }]),
+    };
+    let exported = export_objects(&with_missing, RegionExportOptions::default(), true);
+    assert_eq!(exported.warnings.len(), 1);
+    assert!(matches!(
+        &exported.warnings[0],
+        LoadError::Load { path, .. } if path.to_str() == Some("res\\missing.cpd")
+    ));
+    let json = parse_json(&exported.glb);
+    let children = json["nodes"][0]["children"].as_array().unwrap();
+    assert_eq!(children.len(), 2);
+    let object = &json["nodes"][children[1].as_u64().unwrap() as usize];
+    assert_eq!(object["name"], "res\\empty.cpd");
+}
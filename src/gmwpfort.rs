@@ -1,30 +1,41 @@
 use mint::Vector3;
 use nom::combinator::map;
 use nom::number::complete::{le_u16, le_u32};
-use nom::sequence::tuple;
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
+use crate::error::{finish, IResult, Result};
 use crate::parser_ext::multi::parse_objects_u32;
 use crate::parser_ext::number::vector3_f32;
 use crate::parser_ext::string::sized_string;
-use crate::ttr_closure;
+use crate::region::RegionId;
+use crate::{fields, ttr_closure};
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Gmwpfort {
     pub name: Box<str>,
-    pub region_id: u16,
+    pub region_id: RegionId,
     pub pad: u16,
     pub offset: Vector3<f32>,
     pub world_id: u32,
 }
 
 impl Gmwpfort {
-    pub fn parse<'i>(i: &'i [u8]) -> nom::IResult<&'i [u8], Box<[Gmwpfort]>> {
+    pub fn parse(i: &[u8]) -> Result<Box<[Gmwpfort]>> {
+        finish("Gmwpfort", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Box<[Gmwpfort]>> {
         parse_objects_u32(map(
-            tuple((sized_string, le_u16, le_u16, vector3_f32, le_u32)),
+            fields!(
+                name: sized_string,
+                region_id: RegionId::parse,
+                pad: le_u16,
+                offset: vector3_f32,
+                world_id: le_u32,
+            ),
             ttr_closure! {
                 Gmwpfort {
                     name, region_id, pad, offset, world_id
@@ -1,21 +1,23 @@
 use nom::bytes::complete::tag;
 use nom::character::complete::{char, line_ending, multispace1};
 use nom::combinator::{flat_map, map};
-use nom::multi::{many0, many_m_n};
-use nom::sequence::{delimited, pair, preceded, terminated, tuple};
-use nom::IResult;
+use nom::multi::many0;
+use nom::sequence::{delimited, pair, preceded, terminated};
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
 use std::path::Path;
 
+use crate::error::{finish, IResult, Result};
+use crate::parser_ext::multi::count;
 use crate::parser_ext::text::{
     parse_quoted_path_buf, parse_quoted_string, parse_u16_str, parse_u32_hex_str,
 };
-use crate::ttr_closure;
+use crate::span::context;
+use crate::{fields, ttr_closure};
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, PartialEq)]
 pub struct TileInfo2D {
     pub index: u16,
@@ -28,41 +30,48 @@ pub struct TileInfo2D {
 }
 
 impl TileInfo2D {
-    pub fn parse<'i>(i: &'i str) -> IResult<&'i str, Vec<TileInfo2D>> {
+    pub fn parse(i: &str) -> Result<Vec<TileInfo2D>> {
+        finish("TileInfo2D", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &str) -> IResult<&str, Vec<TileInfo2D>> {
         preceded(
             tag("JMXV2DTI1001\n"),
-            flat_map(terminated(parse_u16_str, line_ending), |count| {
-                many_m_n(count as usize, count as usize, Self::parse_single)
+            flat_map(terminated(parse_u16_str, line_ending), |c| {
+                map(count(Self::parse_single, c as usize), Vec::from)
             }),
         )(i)
     }
 }
 
 impl TileInfo2D {
-    fn parse_single<'a>(i: &'a str) -> IResult<&'a str, Self> {
-        terminated(
-            map(
-                tuple((
-                    parse_u16_str,
-                    preceded(multispace1, parse_u32_hex_str),
-                    preceded(multispace1, parse_quoted_string),
-                    preceded(multispace1, parse_quoted_path_buf),
-                    many0(preceded(
-                        multispace1,
-                        delimited(
-                            char('{'),
-                            pair(terminated(parse_u16_str, char(',')), parse_u16_str),
-                            char('}'),
-                        ),
-                    )),
-                )),
-                ttr_closure! {
-                    TileInfo2D {
-                        index, flag, category, file, extra
-                    }
-                },
+    fn parse_single(i: &str) -> IResult<&str, Self> {
+        context(
+            "TileInfo2D",
+            terminated(
+                map(
+                    fields!(
+                        index: parse_u16_str,
+                        flag: preceded(multispace1, parse_u32_hex_str),
+                        category: preceded(multispace1, parse_quoted_string),
+                        file: preceded(multispace1, parse_quoted_path_buf),
+                        extra: many0(preceded(
+                            multispace1,
+                            delimited(
+                                char('{'),
+                                pair(terminated(parse_u16_str, char(',')), parse_u16_str),
+                                char('}'),
+                            ),
+                        )),
+                    ),
+                    ttr_closure! {
+                        TileInfo2D {
+                            index, flag, category, file, extra
+                        }
+                    },
+                ),
+                line_ending,
             ),
-            line_ending,
         )(i)
     }
 }
@@ -3,91 +3,178 @@ use nom::bytes::complete::tag;
 use nom::combinator::map;
 use nom::error::{make_error, ErrorKind};
 use nom::number::complete::le_u32;
-use nom::sequence::{pair, tuple};
-use nom::IResult;
+use nom::sequence::pair;
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
+use crate::error::{finish, IResult, Result};
+use crate::math::{lerp, quat_normalize, quat_slerp, Transform};
 use crate::parser_ext::complete::take_fixed;
 use crate::parser_ext::multi::parse_objects_u32;
 use crate::parser_ext::number::{vector3_f32, vector4_f32};
 use crate::parser_ext::string::sized_string;
-use crate::ttr_closure;
+use crate::span::context;
+use crate::{fields, ttr_closure};
 
-#[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct KeyFrame {
     pub rotation: Vector4<f32>,
     pub translation: Vector3<f32>,
 }
 
 impl KeyFrame {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            pair(vector4_f32, vector3_f32),
-            ttr_closure! {
-                KeyFrame {
-                    rotation,
-                    translation
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "KeyFrame",
+            map(
+                fields!(
+                    rotation: vector4_f32,
+                    translation: vector3_f32,
+                ),
+                ttr_closure! {
+                    KeyFrame {
+                        rotation,
+                        translation
+                    }
+                },
+            ),
         )(i)
     }
+
+    pub(crate) fn transform(&self) -> Transform {
+        let Vector4 { x, y, z, w } = self.rotation;
+        let Vector3 {
+            x: tx,
+            y: ty,
+            z: tz,
+        } = self.translation;
+        Transform {
+            rotation: quat_normalize([x, y, z, w]),
+            translation: [tx, ty, tz],
+        }
+    }
+
+    /// Interpolates between `self` at `t = 0` and `other` at `t = 1`.
+    pub fn interpolate(&self, other: &KeyFrame, t: f32) -> KeyFrame {
+        let (a, b) = (self.transform(), other.transform());
+        let [x, y, z, w] = quat_slerp(a.rotation, b.rotation, t);
+        let [tx, ty, tz] = lerp(a.translation, b.translation, t);
+        KeyFrame {
+            rotation: Vector4 { x, y, z, w },
+            translation: Vector3 {
+                x: tx,
+                y: ty,
+                z: tz,
+            },
+        }
+    }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AnimatedBone {
     pub name: Box<str>,
     pub keyframes: Box<[KeyFrame]>,
 }
 
 impl AnimatedBone {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            pair(sized_string, parse_objects_u32(KeyFrame::parse)),
-            ttr_closure! {
-                AnimatedBone {
-                    name,
-                    keyframes
-                }
-            },
+    /// The interpolated key frame at `time` in milliseconds, clamped to the key frames, `None` if
+    /// the bone has no key frames.
+    pub fn sample(&self, key_frame_times: &[u32], time: f32) -> Option<KeyFrame> {
+        let len = self.keyframes.len().min(key_frame_times.len());
+        let (times, keyframes) = (&key_frame_times[..len], &self.keyframes[..len]);
+        let next = times.partition_point(|&t| (t as f32) <= time);
+        if next == 0 {
+            return keyframes.first().copied();
+        }
+        if next == len {
+            return keyframes.last().copied();
+        }
+        let (start, end) = (times[next - 1] as f32, times[next] as f32);
+        let t = (time - start) / (end - start);
+        Some(keyframes[next - 1].interpolate(&keyframes[next], t))
+    }
+
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "AnimatedBone",
+            map(
+                fields!(
+                    name: sized_string,
+                    keyframes: context("keyframes", parse_objects_u32(KeyFrame::parse)),
+                ),
+                ttr_closure! {
+                    AnimatedBone {
+                        name,
+                        keyframes
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxAnimation {
     pub unk0: u32,
     pub unk1: u32,
     pub name: Box<str>,
+    /// The length of the animation in milliseconds.
     pub duration: u32,
+    /// The rate the animation was authored at, the key frames carry their own times so sampling
+    /// doesn't depend on it.
     pub frames_per_second: u32,
     pub is_continuous: bool,
+    /// The time of every key frame in milliseconds, the same unit as `duration`.
     pub key_frame_times: Box<[u32]>,
     pub animated_bones: Box<[AnimatedBone]>,
 }
 
 impl JmxAnimation {
-    pub fn parse<'i>(i: &'i [u8]) -> IResult<&'i [u8], Self> {
+    /// The pose at `seconds`, one key frame per bone in `animated_bones`, `None` for bones
+    /// without key frames.
+    ///
+    /// Continuous animations wrap around at `duration`, or at their last key frame if it is zero.
+    /// Others hold their last key frame.
+    pub fn sample(&self, seconds: f32) -> Box<[Option<KeyFrame>]> {
+        let mut time = seconds * 1000.0;
+        let end = match self.duration {
+            0 => self.key_frame_times.last().copied().unwrap_or(0),
+            duration => duration,
+        } as f32;
+        if self.is_continuous && end > 0.0 {
+            time = time.rem_euclid(end);
+        }
+        self.animated_bones
+            .iter()
+            .map(|bone| bone.sample(&self.key_frame_times, time))
+            .collect()
+    }
+
+    pub fn parse(i: &[u8]) -> Result<Self> {
+        finish("JmxAnimation", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Self> {
         let (i, (_, version)) = pair(tag(b"JMXVBAN "), take_fixed::<4>)(i)?;
 
         let (i, (unk0, unk1)) = match &version {
             // b"0101" => (i, (0, 0)), FIXME: something else has changed in this version
-            b"0102" => pair(le_u32, le_u32)(i)?,
+            b"0102" => fields!(unk0: le_u32, unk1: le_u32)(i)?,
             _ => return Err(nom::Err::Failure(make_error(i, ErrorKind::Tag))),
         };
         map(
-            tuple((
-                sized_string,
-                le_u32,
-                le_u32,
-                map(le_u32, |int| int != 0),
-                parse_objects_u32(le_u32),
-                parse_objects_u32(AnimatedBone::parse),
-            )),
+            fields!(
+                name: sized_string,
+                duration: le_u32,
+                frames_per_second: le_u32,
+                is_continuous: map(le_u32, |int| int != 0),
+                key_frame_times: context("key_frame_times", parse_objects_u32(le_u32)),
+                animated_bones: context("animated_bones", parse_objects_u32(AnimatedBone::parse)),
+            ),
             ttr_closure! {
                 unk0, unk1 -> JmxAnimation {
                     name,
@@ -101,3 +188,63 @@ impl JmxAnimation {
         )(i)
     }
 }
+
+#[test]
+fn sample_key_frames() {
+    let quarter = std::f32::consts::FRAC_PI_4;
+    let key_frame = |angle: f32, x: f32| KeyFrame {
+        rotation: Vector4 {
+            x: 0.0,
+            y: (angle / 2.0).sin(),
+            z: 0.0,
+            w: (angle / 2.0).cos(),
+        },
+        translation: Vector3 { x, y: 0.0, z: 0.0 },
+    };
+    let mut animation = JmxAnimation {
+        unk0: 0,
+        unk1: 0,
+        name: "test".into(),
+        duration: 1000,
+        frames_per_second: 30,
+        is_continuous: false,
+        key_frame_times: Box::new([0, 1000]),
+        animated_bones: Box::new([AnimatedBone {
+            name: "bone".into(),
+            keyframes: Box::new([
+                key_frame(0.0, 0.0),
+                key_frame(std::f32::consts::FRAC_PI_2, 2.0),
+            ]),
+        }]),
+    };
+    let translation_x =
+        |animation: &JmxAnimation, seconds| animation.sample(seconds)[0].unwrap().translation.x;
+    let pose = animation.sample(0.5);
+    let half_way = pose[0].unwrap();
+    assert!((half_way.translation.x - 1.0).abs() < 1e-5);
+    assert!((half_way.rotation.y - (quarter / 2.0).sin()).abs() < 1e-5);
+    assert!((half_way.rotation.w - (quarter / 2.0).cos()).abs() < 1e-5);
+    assert!((translation_x(&animation, 1.5) - 2.0).abs() < 1e-5);
+    // the key frame times are milliseconds, 1000 is reached after one second
+    assert!((translation_x(&animation, 1.0) - 2.0).abs() < 1e-5);
+    assert!((translation_x(&animation, 0.001) - 0.002).abs() < 1e-5);
+    animation.is_continuous = true;
+    assert!((translation_x(&animation, 1.25) - 0.5).abs() < 1e-5);
+    // the wrap happens at the duration, the last key frame is held until then
+    animation.duration = 2000;
+    assert!((translation_x(&animation, 1.25) - 2.0).abs() < 1e-5);
+    assert!((translation_x(&animation, 2.25) - 0.5).abs() < 1e-5);
+    animation.duration = 0;
+    assert!((translation_x(&animation, 1.25) - 0.5).abs() < 1e-5);
+
+    // a bone without key frames is left out of the pose instead of becoming the identity
+    let mut bones = std::mem::take(&mut animation.animated_bones).into_vec();
+    bones.push(AnimatedBone {
+        name: "still".into(),
+        keyframes: Box::new([]),
+    });
+    animation.animated_bones = bones.into_boxed_slice();
+    let pose = animation.sample(0.5);
+    assert!(pose[0].is_some());
+    assert!(pose[1].is_none());
+}
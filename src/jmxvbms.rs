@@ -1,23 +1,36 @@
+use std::io::{self, Write};
+use std::ops::Range;
+
 use bitflags::bitflags;
 use mint::{Vector2, Vector3};
-use nom::branch::alt;
 use nom::bytes::complete::tag;
-use nom::combinator::{cond, flat_map, map};
+use nom::combinator::{cond, flat_map, map, verify};
 use nom::number::complete::{le_f32, le_i32, le_u16, le_u32, le_u8};
 use nom::sequence::{pair, preceded, tuple};
-use nom::IResult;
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
+use crate::error::{finish, IResult, Result};
+use crate::jmxvban::{JmxAnimation, KeyFrame};
+use crate::jmxvbsk::JmxSkeleton;
+use crate::math::{quat_rotate, Transform};
+use crate::parser_ext::complete::{at, take_fixed};
 use crate::parser_ext::flags::flags_u32;
 use crate::parser_ext::multi::{count, parse_objects_u32};
 use crate::parser_ext::number::{vector2_f32, vector3_f32, vector6_f32};
 use crate::parser_ext::string::sized_string;
-use crate::ttr_closure;
+use crate::span::context;
+use crate::writer_ext::multi::write_objects_u32;
+use crate::writer_ext::number::{
+    write_f32, write_i32, write_u16, write_u32, write_u8, write_vector2_f32, write_vector3_f32,
+    write_vector6_f32,
+};
+use crate::writer_ext::string::write_sized_string;
+use crate::{fields, ttr_closure};
 
 bitflags! {
-    #[cfg_attr(feature = "serde", derive(Serialize))]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     #[cfg_attr(feature = "serde", serde(transparent))]
     pub struct VertexFlags: u32 {
         const HAS_LIGHT_MAP = 0x400;
@@ -27,7 +40,7 @@ bitflags! {
 }
 
 bitflags! {
-    #[cfg_attr(feature = "serde", derive(Serialize))]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     #[cfg_attr(feature = "serde", serde(transparent))]
     pub struct NavFlags: u32 {
         const UNK0 = 0x1;
@@ -38,7 +51,7 @@ bitflags! {
 }
 
 #[derive(Copy, Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Vertex {
     pub position: Vector3<f32>,
     pub normal: Vector3<f32>,
@@ -51,37 +64,52 @@ pub struct Vertex {
 
 impl Vertex {
     fn parser<'a>(light_map: bool) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                vector3_f32,
-                vector3_f32,
-                vector2_f32,
-                cond(light_map, vector2_f32),
-                le_f32,
-                le_i32,
-                le_i32,
-            )),
-            ttr_closure! {
-                Vertex {
-                    position,
-                    normal,
-                    uv0,
-                    uv1,
-                    float0,
-                    int0,
-                    int1,
-                }
-            },
+        context(
+            "Vertex",
+            map(
+                fields!(
+                    position: vector3_f32,
+                    normal: vector3_f32,
+                    uv0: vector2_f32,
+                    uv1: cond(light_map, vector2_f32),
+                    float0: le_f32,
+                    int0: le_i32,
+                    int1: le_i32,
+                ),
+                ttr_closure! {
+                    Vertex {
+                        position,
+                        normal,
+                        uv0,
+                        uv1,
+                        float0,
+                        int0,
+                        int1,
+                    }
+                },
+            ),
         )
     }
+
+    fn write<W: Write>(&self, w: &mut W, light_map: bool) -> io::Result<()> {
+        write_vector3_f32(w, self.position)?;
+        write_vector3_f32(w, self.normal)?;
+        write_vector2_f32(w, self.uv0)?;
+        if let Some(uv1) = flagged(self.uv1, light_map, "uv1")? {
+            write_vector2_f32(w, uv1)?;
+        }
+        write_f32(w, self.float0)?;
+        write_i32(w, self.int0)?;
+        write_i32(w, self.int1)
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Unknown(pub f32, pub u32);
 
 #[derive(Copy, Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ClothEdge {
     pub vertex_index0: u32,
     pub vertex_index1: u32,
@@ -89,18 +117,31 @@ pub struct ClothEdge {
 }
 
 impl ClothEdge {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((le_u32, le_u32, le_f32)),
-            ttr_closure! {
-                ClothEdge { vertex_index0, vertex_index1, max_distance }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "ClothEdge",
+            map(
+                fields!(
+                    vertex_index0: le_u32,
+                    vertex_index1: le_u32,
+                    max_distance: le_f32,
+                ),
+                ttr_closure! {
+                    ClothEdge { vertex_index0, vertex_index1, max_distance }
+                },
+            ),
         )(i)
     }
+
+    fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_u32(w, self.vertex_index0)?;
+        write_u32(w, self.vertex_index1)?;
+        write_f32(w, self.max_distance)
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ClothSimParams {
     pub unk0: u32,
     pub unk1: f32,
@@ -114,61 +155,110 @@ pub struct ClothSimParams {
 }
 
 impl ClothSimParams {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                le_u32, le_f32, le_f32, le_f32, le_f32, le_f32, le_f32, le_f32, le_u32,
-            )),
-            ttr_closure! {
-                ClothSimParams {
-                    unk0,
-                    unk1,
-                    unk2,
-                    unk3,
-                    unk4,
-                    unk5,
-                    unk6,
-                    unk7,
-                    unk8,
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "ClothSimParams",
+            map(
+                fields!(
+                    unk0: le_u32,
+                    unk1: le_f32,
+                    unk2: le_f32,
+                    unk3: le_f32,
+                    unk4: le_f32,
+                    unk5: le_f32,
+                    unk6: le_f32,
+                    unk7: le_f32,
+                    unk8: le_u32,
+                ),
+                ttr_closure! {
+                    ClothSimParams {
+                        unk0,
+                        unk1,
+                        unk2,
+                        unk3,
+                        unk4,
+                        unk5,
+                        unk6,
+                        unk7,
+                        unk8,
+                    }
+                },
+            ),
         )(i)
     }
+
+    fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_u32(w, self.unk0)?;
+        [
+            self.unk1, self.unk2, self.unk3, self.unk4, self.unk5, self.unk6, self.unk7,
+        ]
+        .into_iter()
+        .try_for_each(|f| write_f32(w, f))?;
+        write_u32(w, self.unk8)
+    }
 }
 
-fn parse_cloth_edges<'a>(
-    i: &'a [u8],
-) -> IResult<&'a [u8], Option<(Box<[ClothEdge]>, Box<[u32]>, ClothSimParams)>> {
+fn parse_cloth_edges(
+    i: &[u8],
+) -> IResult<&[u8], Option<(Box<[ClothEdge]>, Box<[u32]>, ClothSimParams)>> {
     flat_map(le_u32, move |c| {
         cond(
             c != 0,
             tuple((
-                count(ClothEdge::parse, c as usize),
-                count(le_u32, c as usize),
+                context("edges", count(ClothEdge::parse, c as usize)),
+                context("edge_flags", count(le_u32, c as usize)),
                 ClothSimParams::parse,
             )),
         )
     })(i)
 }
 
+fn write_cloth_edges<W: Write>(
+    w: &mut W,
+    cloth_edges: &Option<(Box<[ClothEdge]>, Box<[u32]>, ClothSimParams)>,
+) -> io::Result<()> {
+    match cloth_edges {
+        Some((edges, edge_flags, _)) if edges.len() != edge_flags.len() => Err(invalid_input(
+            "the cloth edges and their flags differ in length",
+        )),
+        Some((edges, edge_flags, params)) => {
+            write_objects_u32(w, edges, |w, edge| edge.write(w))?;
+            edge_flags.iter().try_for_each(|&flag| write_u32(w, flag))?;
+            params.write(w)
+        },
+        None => write_u32(w, 0),
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ClothVertex {
     pub max_distance: f32,
     pub is_pinned: bool,
 }
 
 impl ClothVertex {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((le_f32, map(le_u32, |int| int != 0))),
-            ttr_closure! { ClothVertex { max_distance, is_pinned }},
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "ClothVertex",
+            map(
+                fields!(
+                    max_distance: le_f32,
+                    is_pinned: map(le_u32, |int| int != 0),
+                ),
+                ttr_closure! { ClothVertex { max_distance, is_pinned }},
+            ),
         )(i)
     }
+
+    fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_f32(w, self.max_distance)?;
+        write_u32(w, self.is_pinned as u32)
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BoneIndexData {
     pub index0: u8,
     pub weight0: u16,
@@ -177,12 +267,86 @@ pub struct BoneIndexData {
 }
 
 impl BoneIndexData {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((le_u8, le_u16, le_u8, le_u16)),
-            ttr_closure! { BoneIndexData { index0, weight0, index1, weight1 }},
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "BoneIndexData",
+            map(
+                fields!(
+                    index0: le_u8,
+                    weight0: le_u16,
+                    index1: le_u8,
+                    weight1: le_u16,
+                ),
+                ttr_closure! { BoneIndexData { index0, weight0, index1, weight1 }},
+            ),
         )(i)
     }
+
+    /// The used slots as pairs of an index into the mesh's bone names and a weight, an index of
+    /// `0xFF` marks a slot as unused.
+    pub fn influences(&self) -> impl Iterator<Item = (u8, u16)> {
+        [(self.index0, self.weight0), (self.index1, self.weight1)]
+            .into_iter()
+            .filter(|&(index, _)| index != 0xFF)
+    }
+
+    fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_u8(w, self.index0)?;
+        write_u16(w, self.weight0)?;
+        write_u8(w, self.index1)?;
+        write_u16(w, self.weight1)
+    }
+}
+
+/// A vertex deformed by [`JmxBMesh::skin`].
+#[derive(Copy, Clone, Debug)]
+pub struct SkinnedVertex {
+    pub position: Vector3<f32>,
+    pub normal: Vector3<f32>,
+}
+
+/// Blends the rigid `influences` of a vertex, keeping the vertex as is if there are none.
+fn skin_vertex<'t>(
+    vertex: &Vertex,
+    influences: impl Iterator<Item = (&'t Transform, f32)>,
+) -> SkinnedVertex {
+    let Vector3 { x, y, z } = vertex.position;
+    let Vector3 {
+        x: nx,
+        y: ny,
+        z: nz,
+    } = vertex.normal;
+    let (mut position, mut normal, mut total) = ([0.0f32; 3], [0.0f32; 3], 0.0);
+    for (transform, weight) in influences {
+        let p = transform.transform_point([x, y, z]);
+        let n = quat_rotate(transform.rotation, [nx, ny, nz]);
+        for axis in 0..3 {
+            position[axis] += p[axis] * weight;
+            normal[axis] += n[axis] * weight;
+        }
+        total += weight;
+    }
+    if total <= 0.0 {
+        return SkinnedVertex {
+            position: vertex.position,
+            normal: vertex.normal,
+        };
+    }
+    let len = normal.iter().map(|c| c * c).sum::<f32>().sqrt();
+    let [x, y, z] = position.map(|c| c / total);
+    let [nx, ny, nz] = if len > f32::EPSILON {
+        normal.map(|c| c / len)
+    } else {
+        [nx, ny, nz]
+    };
+    SkinnedVertex {
+        position: Vector3 { x, y, z },
+        normal: Vector3 {
+            x: nx,
+            y: ny,
+            z: nz,
+        },
+    }
 }
 
 fn parse_bones<'a>(
@@ -192,27 +356,51 @@ fn parse_bones<'a>(
         cond(
             bc != 0,
             pair(
-                count(sized_string, bc as usize),
-                count(BoneIndexData::parse, vertex_count),
+                context("bone_names", count(sized_string, bc as usize)),
+                context("bone_indices", count(BoneIndexData::parse, vertex_count)),
             ),
         )
     })
 }
 
+fn write_bones<W: Write>(
+    w: &mut W,
+    bone_data: &Option<(Box<[Box<str>]>, Box<[BoneIndexData]>)>,
+    vertex_count: usize,
+) -> io::Result<()> {
+    match bone_data {
+        Some((_, bone_indices)) if bone_indices.len() != vertex_count => Err(invalid_input(
+            "the bone indices and the vertices differ in length",
+        )),
+        Some((bone_names, bone_indices)) => {
+            write_objects_u32(w, bone_names, |w, name| write_sized_string(w, name))?;
+            bone_indices.iter().try_for_each(|data| data.write(w))
+        },
+        None => write_u32(w, 0),
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Face(pub [u16; 3]);
 
 impl Face {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(tuple((le_u16, le_u16, le_u16)), |data| {
-            Face([data.0, data.1, data.2])
-        })(i)
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "Face",
+            map(tuple((le_u16, le_u16, le_u16)), |data| {
+                Face([data.0, data.1, data.2])
+            }),
+        )(i)
+    }
+
+    fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.0.iter().try_for_each(|&index| write_u16(w, index))
     }
 }
 
 #[derive(Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Gate {
     pub name: Box<str>,
     pub vertices: Box<[Vector3<f32>]>,
@@ -220,24 +408,33 @@ pub struct Gate {
 }
 
 impl Gate {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                sized_string,
-                parse_objects_u32(vector3_f32),
-                parse_objects_u32(Face::parse),
-            )),
-            ttr_closure! {
-                Gate {
-                    name, vertices, faces
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "Gate",
+            map(
+                fields!(
+                    name: sized_string,
+                    vertices: context("vertices", parse_objects_u32(vector3_f32)),
+                    faces: context("faces", parse_objects_u32(Face::parse)),
+                ),
+                ttr_closure! {
+                    Gate {
+                        name, vertices, faces
+                    }
+                },
+            ),
         )(i)
     }
+
+    fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_sized_string(w, &self.name)?;
+        write_objects_u32(w, &self.vertices, |w, &v| write_vector3_f32(w, v))?;
+        write_objects_u32(w, &self.faces, |w, face| face.write(w))
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ObjectLines {
     pub vertex_source: u16,
     pub vertex_destination: u16,
@@ -251,31 +448,46 @@ pub struct ObjectLines {
 
 impl ObjectLines {
     fn parser<'a>(nav_flag: NavFlags) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                le_u16,
-                le_u16,
-                le_u16,
-                le_u16,
-                le_u8,
-                cond(nav_flag.contains(NavFlags::UNK0), le_u8),
-            )),
-            ttr_closure! {
-                ObjectLines {
-                    vertex_source,
-                    vertex_destination,
-                    cell_source,
-                    cell_destination,
-                    collision_flag,
-                    unk,
-                }
-            },
+        context(
+            "ObjectLines",
+            map(
+                fields!(
+                    vertex_source: le_u16,
+                    vertex_destination: le_u16,
+                    cell_source: le_u16,
+                    cell_destination: le_u16,
+                    collision_flag: le_u8,
+                    unk: cond(nav_flag.contains(NavFlags::UNK0), le_u8),
+                ),
+                ttr_closure! {
+                    ObjectLines {
+                        vertex_source,
+                        vertex_destination,
+                        cell_source,
+                        cell_destination,
+                        collision_flag,
+                        unk,
+                    }
+                },
+            ),
         )
     }
+
+    fn write<W: Write>(&self, w: &mut W, nav_flag: NavFlags) -> io::Result<()> {
+        write_u16(w, self.vertex_source)?;
+        write_u16(w, self.vertex_destination)?;
+        write_u16(w, self.cell_source)?;
+        write_u16(w, self.cell_destination)?;
+        write_u8(w, self.collision_flag)?;
+        if let Some(unk) = flagged(self.unk, nav_flag.contains(NavFlags::UNK0), "unk")? {
+            write_u8(w, unk)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NavMesh {
     pub vertices: Box<[(Vector3<f32>, u8)]>,
     pub ground: Box<[(Face, u16, Option<u8>)]>,
@@ -291,49 +503,87 @@ pub struct NavMesh {
 
 impl NavMesh {
     fn parser<'a>(nav_flag: NavFlags) -> impl FnMut(&'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                parse_objects_u32(pair(vector3_f32, le_u8)),
-                parse_objects_u32(tuple((
-                    Face::parse,
-                    le_u16,
-                    cond(nav_flag.contains(NavFlags::UNK1), le_u8),
-                ))),
-                parse_objects_u32(ObjectLines::parser(nav_flag)),
-                parse_objects_u32(ObjectLines::parser(nav_flag)),
-                map(
-                    cond(
-                        nav_flag.contains(NavFlags::UNK2),
-                        parse_objects_u32(sized_string),
+        context(
+            "NavMesh",
+            map(
+                fields!(
+                    vertices: context("vertices", parse_objects_u32(pair(vector3_f32, le_u8))),
+                    ground: context(
+                        "ground",
+                        parse_objects_u32(tuple((
+                            Face::parse,
+                            le_u16,
+                            cond(nav_flag.contains(NavFlags::UNK1), le_u8),
+                        ))),
                     ),
-                    Option::unwrap_or_default,
+                    outlines: context("outlines", parse_objects_u32(ObjectLines::parser(nav_flag))),
+                    inlines: context("inlines", parse_objects_u32(ObjectLines::parser(nav_flag))),
+                    event: context(
+                        "event",
+                        map(
+                            cond(
+                                nav_flag.contains(NavFlags::UNK2),
+                                parse_objects_u32(sized_string),
+                            ),
+                            Option::unwrap_or_default,
+                        ),
+                    ),
+                    unk0: le_f32,
+                    unk1: le_f32,
+                    unk2: le_u32,
+                    unk3: le_u32,
+                    unk4: context("unk4", parse_objects_u32(parse_objects_u32(le_u16))),
                 ),
-                le_f32,
-                le_f32,
-                le_u32,
-                le_u32,
-                parse_objects_u32(parse_objects_u32(le_u16)),
-            )),
-            ttr_closure! {
-                NavMesh {
-                    vertices,
-                    ground,
-                    outlines,
-                    inlines,
-                    event,
-                    unk0,
-                    unk1,
-                    unk2,
-                    unk3,
-                    unk4,
-                }
-            },
+                ttr_closure! {
+                    NavMesh {
+                        vertices,
+                        ground,
+                        outlines,
+                        inlines,
+                        event,
+                        unk0,
+                        unk1,
+                        unk2,
+                        unk3,
+                        unk4,
+                    }
+                },
+            ),
         )
     }
+
+    fn write<W: Write>(&self, w: &mut W, nav_flag: NavFlags) -> io::Result<()> {
+        write_objects_u32(w, &self.vertices, |w, &(v, unk)| {
+            write_vector3_f32(w, v)?;
+            write_u8(w, unk)
+        })?;
+        write_objects_u32(w, &self.ground, |w, (face, unk0, unk1)| {
+            face.write(w)?;
+            write_u16(w, *unk0)?;
+            if let Some(unk1) = flagged(*unk1, nav_flag.contains(NavFlags::UNK1), "ground")? {
+                write_u8(w, unk1)?;
+            }
+            Ok(())
+        })?;
+        write_objects_u32(w, &self.outlines, |w, line| line.write(w, nav_flag))?;
+        write_objects_u32(w, &self.inlines, |w, line| line.write(w, nav_flag))?;
+        if nav_flag.contains(NavFlags::UNK2) {
+            write_objects_u32(w, &self.event, |w, event| write_sized_string(w, event))?;
+        } else if !self.event.is_empty() {
+            return Err(invalid_input("event is set without its nav flag"));
+        }
+        write_f32(w, self.unk0)?;
+        write_f32(w, self.unk1)?;
+        write_u32(w, self.unk2)?;
+        write_u32(w, self.unk3)?;
+        write_objects_u32(w, &self.unk4, |w, unk| {
+            write_objects_u32(w, unk, |w, &v| write_u16(w, v))
+        })
+    }
 }
 
 #[derive(Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxBMesh {
     pub header: JmxBMeshHeader,
     pub vertices: Box<[Vertex]>,
@@ -345,26 +595,98 @@ pub struct JmxBMesh {
     pub bounding_box: [f32; 6],
     pub gates: Box<[Gate]>,
     pub nav_mesh: Option<NavMesh>,
+    /// The bytes between and after the sections that none of them covers, like padding, by their
+    /// offset, so that [`write`](Self::write) can put them back.
+    pub unread: Box<[(u32, Box<[u8]>)]>,
 }
 
 impl JmxBMesh {
-    pub fn parse<'i>(i: &'i [u8]) -> IResult<&'i [u8], Self> {
-        let (_, header) = JmxBMeshHeader::parse(i)?;
+    /// Deforms the vertices into `pose` of `animation`, as returned by [`JmxAnimation::sample`],
+    /// using linear blend skinning.
+    ///
+    /// The weights of a vertex are normalized over the slots referring to bones found in
+    /// `skeleton`, vertices without any such slot keep their bind pose position and normal.
+    pub fn skin(
+        &self,
+        skeleton: &JmxSkeleton,
+        animation: &JmxAnimation,
+        pose: &[Option<KeyFrame>],
+    ) -> Box<[SkinnedVertex]> {
+        let posed = skeleton.posed_world_transforms(animation, pose);
+        let skin_transforms = skeleton
+            .world_transforms()
+            .iter()
+            .zip(posed.iter())
+            .map(|(bind, posed)| posed.mul(&bind.inverse()))
+            .collect::<Vec<_>>();
+        let bones = skeleton.mesh_bone_indices(self);
+        let bone_indices = self.bone_data.as_ref().map_or(&[][..], |(_, data)| data);
+        self.vertices
+            .iter()
+            .enumerate()
+            .map(|(idx, vertex)| {
+                let influences = bone_indices
+                    .get(idx)
+                    .into_iter()
+                    .flat_map(BoneIndexData::influences)
+                    .filter_map(|(index, weight)| {
+                        let bone = bones.get(index as usize).copied().flatten()?;
+                        Some((&skin_transforms[bone], f32::from(weight)))
+                    });
+                skin_vertex(vertex, influences)
+            })
+            .collect()
+    }
+
+    pub fn parse(i: &[u8]) -> Result<Self> {
+        finish("JmxBMesh", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Self> {
+        // the ranges read by the parsers, to keep the bytes in between
+        let mut read = Vec::new();
+        let mut section =
+            |offset: u32, rest: &[u8]| read.push(offset as usize..i.len() - rest.len());
+        let (rest, header) = JmxBMeshHeader::parse(i)?;
+        section(0, rest);
         let has_light_map = header.vertex_flags.contains(VertexFlags::HAS_LIGHT_MAP);
-        let (_, (vertices, light_map_path)) = pair(
-            parse_objects_u32(Vertex::parser(has_light_map)),
-            cond(has_light_map, sized_string),
-        )(&i[header.vertex as usize..])?;
-        let (_, bone_data) = parse_bones(vertices.len())(&i[header.skin as usize..])?;
-        let (_, faces) = parse_objects_u32(Face::parse)(&i[header.face as usize..])?;
-        let (_, cloth_vertex) =
-            parse_objects_u32(ClothVertex::parse)(&i[header.cloth_vertex as usize..])?;
-        let (_, cloth_edges) = parse_cloth_edges(&i[header.cloth_edge as usize..])?;
-        let (_, bounding_box) = vector6_f32(&i[header.bounding_box as usize..])?;
-        let (_, gates) = parse_objects_u32(Gate::parse)(&i[header.gate as usize..])?;
-        let (_, nav_mesh) = cond(header.nav_mesh != 0, NavMesh::parser(header.nav_flags))(
-            &i[header.nav_mesh as usize..],
-        )?;
+        let (rest, (vertices, light_map_path)) = at(
+            header.vertex,
+            pair(
+                context("vertices", parse_objects_u32(Vertex::parser(has_light_map))),
+                context("light_map_path", cond(has_light_map, sized_string)),
+            ),
+        )(i)?;
+        section(header.vertex, rest);
+        let (rest, bone_data) =
+            context("bone_data", at(header.skin, parse_bones(vertices.len())))(i)?;
+        section(header.skin, rest);
+        let (rest, faces) = context("faces", at(header.face, parse_objects_u32(Face::parse)))(i)?;
+        section(header.face, rest);
+        let (rest, cloth_vertex) = context(
+            "cloth_vertex",
+            at(header.cloth_vertex, parse_objects_u32(ClothVertex::parse)),
+        )(i)?;
+        section(header.cloth_vertex, rest);
+        let (rest, cloth_edges) =
+            context("cloth_edges", at(header.cloth_edge, parse_cloth_edges))(i)?;
+        section(header.cloth_edge, rest);
+        let (rest, bounding_box) =
+            context("bounding_box", at(header.bounding_box, vector6_f32))(i)?;
+        section(header.bounding_box, rest);
+        let (rest, gates) = context("gates", at(header.gate, parse_objects_u32(Gate::parse)))(i)?;
+        section(header.gate, rest);
+        let (rest, nav_mesh) = cond(
+            header.nav_mesh != 0,
+            at(header.nav_mesh, NavMesh::parser(header.nav_flags)),
+        )(i)?;
+        if nav_mesh.is_some() {
+            section(header.nav_mesh, rest);
+        }
+        let unread = unread_ranges(i.len(), read)
+            .into_iter()
+            .map(|range| (range.start as u32, i[range].into()))
+            .collect();
 
         Ok((
             &[],
@@ -379,14 +701,169 @@ impl JmxBMesh {
                 bounding_box,
                 gates,
                 nav_mesh,
+                unread,
             },
         ))
     }
+
+    /// Writes the mesh.
+    ///
+    /// The sections are written at the offsets of the header if they still fit there, with the
+    /// [`unread`](Self::unread) bytes put back between and after them and any other gaps filled
+    /// with zeros, so that a parsed mesh is written back as it was read.
+    /// Otherwise, e.g. for meshes built from scratch or sections that grew, they are laid out in
+    /// header order right after the header, the offsets are recomputed and the unread bytes are
+    /// left out.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidInput`] if an optional field doesn't match the flag of
+    /// the header deciding whether it is present, or if the lengths of related slices differ.
+    pub fn write(&self, w: &mut impl Write) -> io::Result<()> {
+        let has_light_map = self
+            .header
+            .vertex_flags
+            .contains(VertexFlags::HAS_LIGHT_MAP);
+        let nav_flags = self.header.nav_flags;
+
+        let mut vertex = Vec::new();
+        write_objects_u32(&mut vertex, &self.vertices, |w, v| {
+            v.write(w, has_light_map)
+        })?;
+        let light_map_path = self.light_map_path.as_deref();
+        if let Some(path) = flagged(light_map_path, has_light_map, "light_map_path")? {
+            write_sized_string(&mut vertex, path)?;
+        }
+        let mut skin = Vec::new();
+        write_bones(&mut skin, &self.bone_data, self.vertices.len())?;
+        let mut face = Vec::new();
+        write_objects_u32(&mut face, &self.faces, |w, face| face.write(w))?;
+        let mut cloth_vertex = Vec::new();
+        write_objects_u32(&mut cloth_vertex, &self.cloth_vertex, |w, v| v.write(w))?;
+        let mut cloth_edge = Vec::new();
+        write_cloth_edges(&mut cloth_edge, &self.cloth_edges)?;
+        let mut bounding_box = Vec::new();
+        write_vector6_f32(&mut bounding_box, &self.bounding_box)?;
+        let mut gate = Vec::new();
+        write_objects_u32(&mut gate, &self.gates, |w, gate| gate.write(w))?;
+        let nav_mesh = match &self.nav_mesh {
+            Some(nav_mesh) => {
+                let mut data = Vec::new();
+                nav_mesh.write(&mut data, nav_flags)?;
+                Some(data)
+            },
+            None => None,
+        };
+
+        let mut header = self.header.clone();
+        if nav_mesh.is_none() {
+            header.nav_mesh = 0;
+        }
+        // the header size only depends on its strings, so measure it before placing the sections
+        let mut head = Vec::new();
+        header.write(&mut head)?;
+        let header_len = head.len();
+
+        let mut sections = vec![
+            (&mut header.vertex, vertex),
+            (&mut header.skin, skin),
+            (&mut header.face, face),
+            (&mut header.cloth_vertex, cloth_vertex),
+            (&mut header.cloth_edge, cloth_edge),
+            (&mut header.bounding_box, bounding_box),
+            (&mut header.gate, gate),
+        ];
+        if let Some(nav_mesh) = nav_mesh {
+            sections.push((&mut header.nav_mesh, nav_mesh));
+        }
+        let mut order = (0..sections.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&idx| *sections[idx].0);
+        let mut end = header_len;
+        let fits = order.iter().all(|&idx| {
+            let (offset, data) = &sections[idx];
+            let start = **offset as usize;
+            let fits = start >= end;
+            end = start.saturating_add(data.len());
+            fits
+        });
+        if !fits {
+            order = (0..sections.len()).collect();
+            let mut offset = header_len;
+            for (section_offset, data) in &mut sections {
+                **section_offset = offset as u32;
+                offset += data.len();
+            }
+        }
+        let mut body = Vec::new();
+        let mut written = Vec::new();
+        for idx in order {
+            let (offset, data) = &sections[idx];
+            let start = **offset as usize;
+            body.resize(start - header_len, 0);
+            body.extend_from_slice(data);
+            written.push(start..start + data.len());
+        }
+        drop(sections);
+        if fits {
+            // put the unread bytes back where they don't collide with the header or a grown section
+            for (offset, data) in self.unread.iter() {
+                let start = *offset as usize;
+                let range = start..start + data.len();
+                let collides = written.iter().any(|section: &Range<_>| {
+                    section.start < range.end && range.start < section.end
+                });
+                if start < header_len || collides {
+                    continue;
+                }
+                let (start, end) = (start - header_len, range.end - header_len);
+                if body.len() < end {
+                    body.resize(end, 0);
+                }
+                body[start..end].copy_from_slice(data);
+            }
+        }
+
+        head.clear();
+        header.write(&mut head)?;
+        w.write_all(&head)?;
+        w.write_all(&body)
+    }
+}
+
+/// The ranges of a file of `len` bytes not covered by any of the `read` ones.
+fn unread_ranges(len: usize, mut read: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    read.sort_by_key(|range| range.start);
+    let mut unread = Vec::new();
+    let mut end = 0;
+    for range in read.into_iter().chain(std::iter::once(len..len)) {
+        if range.start > end {
+            unread.push(end..range.start);
+        }
+        end = end.max(range.end);
+    }
+    unread
+}
+
+fn invalid_input(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+/// Returns `value` if it is present exactly when the `flag` deciding whether `field` is written
+/// is set.
+fn flagged<T>(value: Option<T>, flag: bool, field: &str) -> io::Result<Option<T>> {
+    match (&value, flag) {
+        (Some(_), false) => Err(invalid_input(&format!("{} is set without its flag", field))),
+        (None, true) => Err(invalid_input(&format!(
+            "{} is missing although its flag is set",
+            field
+        ))),
+        _ => Ok(value),
+    }
 }
 
 #[derive(Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxBMeshHeader {
+    /// Either `0109` or `0110`.
+    pub version: [u8; 4],
     pub vertex: u32,
     pub skin: u32,
     pub face: u32,
@@ -408,53 +885,458 @@ pub struct JmxBMeshHeader {
 }
 
 impl JmxBMeshHeader {
-    pub fn parse(i: &[u8]) -> IResult<&[u8], Self> {
-        preceded(
-            alt((tag(b"JMXVBMS 0109"), tag(b"JMXVBMS 0110"))),
-            map(
-                tuple((
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    flags_u32(NavFlags::from_bits),
-                    le_u32,
-                    flags_u32(VertexFlags::from_bits),
-                    le_u32,
-                    sized_string,
-                    sized_string,
-                    le_u32,
-                )),
-                ttr_closure! {
-                    JmxBMeshHeader {
-                        vertex,
-                        skin,
-                        face,
-                        cloth_vertex,
-                        cloth_edge,
-                        bounding_box,
-                        gate,
-                        nav_mesh,
-                        unk0,
-                        unk1,
-                        unk3,
-                        nav_flags,
-                        sub_prim_count,
-                        vertex_flags,
-                        unk4,
-                        name,
-                        material,
-                        unk5
-                    }
-                },
+    pub(crate) fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "JmxBMeshHeader",
+            preceded(
+                tag(b"JMXVBMS "),
+                map(
+                    fields!(
+                        version: verify(take_fixed::<4>, |version: &[u8; 4]| {
+                            version == b"0109" || version == b"0110"
+                        }),
+                        vertex: le_u32,
+                        skin: le_u32,
+                        face: le_u32,
+                        cloth_vertex: le_u32,
+                        cloth_edge: le_u32,
+                        bounding_box: le_u32,
+                        gate: le_u32,
+                        nav_mesh: le_u32,
+                        unk0: le_u32,
+                        unk1: le_u32,
+                        unk3: le_u32,
+                        nav_flags: flags_u32(NavFlags::from_bits_unchecked),
+                        sub_prim_count: le_u32,
+                        vertex_flags: flags_u32(VertexFlags::from_bits_unchecked),
+                        unk4: le_u32,
+                        name: sized_string,
+                        material: sized_string,
+                        unk5: le_u32,
+                    ),
+                    ttr_closure! {
+                        JmxBMeshHeader {
+                            version,
+                            vertex,
+                            skin,
+                            face,
+                            cloth_vertex,
+                            cloth_edge,
+                            bounding_box,
+                            gate,
+                            nav_mesh,
+                            unk0,
+                            unk1,
+                            unk3,
+                            nav_flags,
+                            sub_prim_count,
+                            vertex_flags,
+                            unk4,
+                            name,
+                            material,
+                            unk5
+                        }
+                    },
+                ),
             ),
         )(i)
     }
+
+    fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(b"JMXVBMS ")?;
+        w.write_all(&self.version)?;
+        [
+            self.vertex,
+            self.skin,
+            self.face,
+            self.cloth_vertex,
+            self.cloth_edge,
+            self.bounding_box,
+            self.gate,
+            self.nav_mesh,
+            self.unk0,
+            self.unk1,
+            self.unk3,
+            self.nav_flags.bits(),
+            self.sub_prim_count,
+            self.vertex_flags.bits(),
+            self.unk4,
+        ]
+        .into_iter()
+        .try_for_each(|v| write_u32(w, v))?;
+        write_sized_string(w, &self.name)?;
+        write_sized_string(w, &self.material)?;
+        write_u32(w, self.unk5)
+    }
+}
+
+#[test]
+fn out_of_bounds_section_offset() {
+    let mut file = b"JMXVBMS 0110".to_vec();
+    file.extend_from_slice(&0xFFFFu32.to_le_bytes());
+    file.resize(file.len() + 4 * 17, 0);
+    let err = JmxBMesh::parse(&file).unwrap_err();
+    assert_eq!(err.kind(), &crate::ErrorKind::InvalidOffset(0xFFFF));
+    assert_eq!(err.offset(), file.len());
+}
+
+#[test]
+fn write_round_trip() {
+    let v3 = |x, y, z| Vector3 { x, y, z };
+    let vertex = |x| Vertex {
+        position: v3(x, 1.0, 2.0),
+        normal: v3(0.0, 1.0, 0.0),
+        uv0: Vector2 { x: 0.5, y: 0.25 },
+        uv1: Some(Vector2 { x: 0.75, y: 1.0 }),
+        float0: 3.0,
+        int0: -1,
+        int1: 7,
+    };
+    let line = ObjectLines {
+        vertex_source: 0,
+        vertex_destination: 1,
+        cell_source: 0,
+        cell_destination: 0xFFFF,
+        collision_flag: 3,
+        unk: Some(9),
+    };
+    let mesh = JmxBMesh {
+        header: JmxBMeshHeader {
+            version: *b"0110",
+            vertex: 0,
+            skin: 0,
+            face: 0,
+            cloth_vertex: 0,
+            cloth_edge: 0,
+            bounding_box: 0,
+            gate: 0,
+            nav_mesh: 0,
+            unk0: 0,
+            unk1: 0,
+            unk3: 1,
+            nav_flags: NavFlags::all(),
+            sub_prim_count: 1,
+            vertex_flags: VertexFlags::HAS_LIGHT_MAP,
+            unk4: 0,
+            name: "mesh".into(),
+            material: "material".into(),
+            unk5: 0,
+        },
+        vertices: Box::new([vertex(0.0), vertex(1.0), vertex(2.0)]),
+        light_map_path: Some("light.ddj".into()),
+        bone_data: Some((
+            Box::new(["Bip01".into()]),
+            Box::new(
+                [BoneIndexData {
+                    index0: 0,
+                    weight0: 0xFFFF,
+                    index1: 0xFF,
+                    weight1: 0,
+                }; 3],
+            ),
+        )),
+        faces: Box::new([Face([0, 1, 2])]),
+        cloth_vertex: Box::new([ClothVertex {
+            max_distance: 1.0,
+            is_pinned: true,
+        }]),
+        cloth_edges: Some((
+            Box::new([ClothEdge {
+                vertex_index0: 0,
+                vertex_index1: 1,
+                max_distance: 2.0,
+            }]),
+            Box::new([1]),
+            ClothSimParams {
+                unk0: 1,
+                unk1: 2.0,
+                unk2: 3.0,
+                unk3: 4.0,
+                unk4: 5.0,
+                unk5: 6.0,
+                unk6: 7.0,
+                unk7: 8.0,
+                unk8: 9,
+            },
+        )),
+        bounding_box: [0.0, 1.0, 2.0, 3.0, 4.0, 5.0],
+        gates: Box::new([Gate {
+            name: "gate".into(),
+            vertices: Box::new([v3(0.0, 0.0, 0.0)]),
+            faces: Box::new([Face([0, 0, 0])]),
+        }]),
+        nav_mesh: Some(NavMesh {
+            vertices: Box::new([(v3(0.0, 0.0, 0.0), 1), (v3(1.0, 0.0, 0.0), 2)]),
+            ground: Box::new([(Face([0, 1, 0]), 4, Some(5))]),
+            outlines: Box::new([line]),
+            inlines: Box::new([line, line]),
+            event: Box::new(["event".into()]),
+            unk0: 1.0,
+            unk1: 2.0,
+            unk2: 3,
+            unk3: 4,
+            unk4: Box::new([Box::new([1, 2]), Box::new([])]),
+        }),
+        unread: Box::new([]),
+    };
+
+    let mut written = Vec::new();
+    mesh.write(&mut written).unwrap();
+    let parsed = JmxBMesh::parse(&written).unwrap();
+    assert_eq!(parsed.header.vertex, 0x60);
+    assert_eq!(parsed.gates[0].name, mesh.gates[0].name);
+    let mut rewritten = Vec::new();
+    parsed.write(&mut rewritten).unwrap();
+    assert_eq!(written, rewritten);
+}
+
+#[test]
+fn write_keeps_original_layout() {
+    let u32s = |values: &[u32]| {
+        values
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>()
+    };
+    let f32s = |count| {
+        (0..count)
+            .flat_map(|v| (v as f32).to_le_bytes())
+            .collect::<Vec<_>>()
+    };
+    // the faces precede the vertices and are padded to a multiple of four, and the file ends in
+    // bytes no section covers
+    let mut file = b"JMXVBMS 0110".to_vec();
+    file.extend(u32s(&[
+        96, 144, 85, 148, 152, 156, 180, 0, 0, 0, 0, 0, 1, 0, 0,
+    ]));
+    file.extend(u32s(&[1]));
+    file.push(b'm');
+    file.extend(u32s(&[0, 0]));
+    assert_eq!(file.len(), 85);
+    file.extend(u32s(&[1]));
+    file.extend([0, 0, 1, 0, 2, 0, 0xcd]);
+    file.extend(u32s(&[1]));
+    file.extend(f32s(9));
+    file.extend(u32s(&[5, 6]));
+    // bones, cloth vertices and cloth edges
+    file.extend(u32s(&[0, 0, 0]));
+    file.extend(f32s(6));
+    // gates
+    file.extend(u32s(&[0]));
+    file.extend([0xde, 0xad]);
+
+    let mesh = JmxBMesh::parse(&file).unwrap();
+    assert_eq!((mesh.header.face, mesh.header.vertex), (85, 96));
+    assert_eq!(
+        &*mesh.unread,
+        [(95, [0xcd].into()), (184, [0xde, 0xad].into())]
+    );
+    let mut written = Vec::new();
+    mesh.write(&mut written).unwrap();
+    assert_eq!(written, file);
+
+    let mut grown = mesh.clone();
+    grown.faces = vec![Face([0, 0, 0]); 2].into();
+    written.clear();
+    grown.write(&mut written).unwrap();
+    let parsed = JmxBMesh::parse(&written).unwrap();
+    assert_eq!((parsed.header.vertex, parsed.header.face), (85, 137));
+    assert_eq!(parsed.faces.len(), 2);
+
+    let mut light_mapped = mesh;
+    light_mapped.header.vertex_flags = VertexFlags::HAS_LIGHT_MAP;
+    let err = light_mapped.write(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn skin_vertex_blends_influences() {
+    let vertex = Vertex {
+        position: Vector3 {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        },
+        normal: Vector3 {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        },
+        uv0: Vector2 { x: 0.0, y: 0.0 },
+        uv1: None,
+        float0: 0.0,
+        int0: 0,
+        int1: 0,
+    };
+    let half = std::f32::consts::FRAC_1_SQRT_2;
+    let moved = Transform {
+        rotation: [0.0, 0.0, 0.0, 1.0],
+        translation: [0.0, 2.0, 0.0],
+    };
+    // 90 degrees around z, turning +x into +y
+    let rotated = Transform {
+        rotation: [0.0, 0.0, half, half],
+        translation: [0.0, 0.0, 0.0],
+    };
+    let skinned = skin_vertex(&vertex, [(&moved, 3.0), (&rotated, 1.0)].into_iter());
+    let position = [skinned.position.x, skinned.position.y, skinned.position.z];
+    assert!(position
+        .iter()
+        .zip([0.75, 1.75, 0.0])
+        .all(|(a, b)| (a - b).abs() < 1e-5));
+    let normal = [skinned.normal.x, skinned.normal.y, skinned.normal.z];
+    let len = (0.75f32 * 0.75 + 0.25 * 0.25).sqrt();
+    assert!(normal
+        .iter()
+        .zip([0.75 / len, 0.25 / len, 0.0])
+        .all(|(a, b)| (a - b).abs() < 1e-5));
+    assert_eq!(
+        skin_vertex(&vertex, std::iter::empty()).position,
+        vertex.position
+    );
+    let unused = BoneIndexData {
+        index0: 0xFF,
+        weight0: 0xFFFF,
+        index1: 1,
+        weight1: 2,
+    };
+    assert_eq!(unused.influences().collect::<Vec<_>>(), [(1, 2)]);
+}
+
+#[test]
+fn skin_keeps_bind_pose_of_bones_without_key_frames() {
+    use crate::jmxvban::AnimatedBone;
+    use crate::jmxvbsk::Bone;
+    use mint::Vector4;
+
+    let zero = Vector3 {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+    };
+    let identity = Vector4 {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+        w: 1.0,
+    };
+    let bone = |name: &str, parent: &str, translation| Bone {
+        unk: 0,
+        name: name.into(),
+        parent_name: parent.into(),
+        rotation_to_parent: identity,
+        translation_to_parent: translation,
+        rotation_to_origin: identity,
+        translation_to_origin: zero,
+        rotation_to_unknown: identity,
+        translation_to_unknown: zero,
+        children: Box::new([]),
+    };
+    let skeleton = JmxSkeleton {
+        bones: Box::new([
+            bone("root", "", zero),
+            bone(
+                "child",
+                "root",
+                Vector3 {
+                    x: 1.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+            ),
+        ]),
+        unk0: 0,
+        unk1: 0,
+    };
+    // only the root moves, the child has a track without key frames
+    let animation = JmxAnimation {
+        unk0: 0,
+        unk1: 0,
+        name: "lift".into(),
+        duration: 0,
+        frames_per_second: 30,
+        is_continuous: false,
+        key_frame_times: Box::new([0]),
+        animated_bones: Box::new([
+            AnimatedBone {
+                name: "root".into(),
+                keyframes: Box::new([KeyFrame {
+                    rotation: identity,
+                    translation: Vector3 {
+                        x: 0.0,
+                        y: 2.0,
+                        z: 0.0,
+                    },
+                }]),
+            },
+            AnimatedBone {
+                name: "child".into(),
+                keyframes: Box::new([]),
+            },
+        ]),
+    };
+    let mesh = JmxBMesh {
+        header: JmxBMeshHeader {
+            version: *b"0110",
+            vertex: 0,
+            skin: 0,
+            face: 0,
+            cloth_vertex: 0,
+            cloth_edge: 0,
+            bounding_box: 0,
+            gate: 0,
+            nav_mesh: 0,
+            unk0: 0,
+            unk1: 0,
+            unk3: 0,
+            nav_flags: NavFlags::empty(),
+            sub_prim_count: 0,
+            vertex_flags: VertexFlags::empty(),
+            unk4: 0,
+            name: "m".into(),
+            material: "".into(),
+            unk5: 0,
+        },
+        vertices: Box::new([Vertex {
+            position: Vector3 {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            normal: Vector3 {
+                x: 1.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            uv0: Vector2 { x: 0.0, y: 0.0 },
+            uv1: None,
+            float0: 0.0,
+            int0: 0,
+            int1: 0,
+        }]),
+        light_map_path: None,
+        bone_data: Some((
+            Box::new(["child".into()]),
+            Box::new([BoneIndexData {
+                index0: 0,
+                weight0: 1000,
+                index1: 0xFF,
+                weight1: 0,
+            }]),
+        )),
+        faces: Box::new([]),
+        cloth_vertex: Box::new([]),
+        cloth_edges: None,
+        bounding_box: [0.0; 6],
+        gates: Box::new([]),
+        nav_mesh: None,
+        unread: Box::new([]),
+    };
+    let skinned = mesh.skin(&skeleton, &animation, &animation.sample(0.0));
+    // the child follows the root up while keeping its bind offset
+    let position = skinned[0].position;
+    assert!([position.x, position.y, position.z]
+        .iter()
+        .zip([1.0, 2.0, 0.0])
+        .all(|(a, b)| (a - b).abs() < 1e-5));
 }
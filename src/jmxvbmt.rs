@@ -5,20 +5,21 @@ use mint::Vector4;
 use nom::bytes::complete::tag;
 use nom::combinator::map;
 use nom::number::complete::{le_f32, le_u16, le_u32, le_u8};
-use nom::sequence::{pair, preceded, tuple};
-use nom::IResult;
+use nom::sequence::{pair, preceded};
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
+use crate::error::{finish, IResult, Result};
 use crate::parser_ext::flags::flags_u32;
 use crate::parser_ext::multi::parse_objects_u32;
 use crate::parser_ext::number::vector4_f32;
 use crate::parser_ext::string::{sized_path, sized_string};
-use crate::ttr_closure;
+use crate::span::context;
+use crate::{fields, ttr_closure};
 
 bitflags! {
-    #[cfg_attr(feature = "serde", derive(Serialize))]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     #[cfg_attr(feature = "serde", serde(transparent))]
     pub struct MaterialFlags: u32 {
         const UNK0 = 0x1;
@@ -39,7 +40,7 @@ bitflags! {
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Material {
     pub name: Box<str>,
     pub diffuse: Vector4<f32>,
@@ -52,46 +53,48 @@ pub struct Material {
     pub unk0: f32,
     pub unk1: u16,
     pub absolute_diffuse_map_path: bool,
-    #[cfg_attr(feature = "serde", serde(skip))]
     pub normal_map: Option<(Box<Path>, u32)>,
 }
 
 impl Material {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        let (i, mut mat) = map(
-            tuple((
-                sized_string,
-                vector4_f32,
-                vector4_f32,
-                vector4_f32,
-                vector4_f32,
-                le_f32,
-                flags_u32(MaterialFlags::from_bits),
-                sized_path,
-                le_f32,
-                le_u16,
-                map(le_u8, |b| b != 0),
-                |i| IResult::Ok((i, None)),
-            )),
-            ttr_closure! {
-                Material {
-                    name,
-                    diffuse,
-                    ambient,
-                    specular,
-                    emissive,
-                    specular_power,
-                    material_flags,
-                    diffuse_map,
-                    unk0,
-                    unk1,
-                    absolute_diffuse_map_path,
-                    normal_map
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        let (i, mut mat) = context(
+            "Material",
+            map(
+                fields!(
+                    name: sized_string,
+                    diffuse: vector4_f32,
+                    ambient: vector4_f32,
+                    specular: vector4_f32,
+                    emissive: vector4_f32,
+                    specular_power: le_f32,
+                    material_flags: flags_u32(MaterialFlags::from_bits_unchecked),
+                    diffuse_map: sized_path,
+                    unk0: le_f32,
+                    unk1: le_u16,
+                    absolute_diffuse_map_path: map(le_u8, |b| b != 0),
+                    normal_map: |i| IResult::Ok((i, None)),
+                ),
+                ttr_closure! {
+                    Material {
+                        name,
+                        diffuse,
+                        ambient,
+                        specular,
+                        emissive,
+                        specular_power,
+                        material_flags,
+                        diffuse_map,
+                        unk0,
+                        unk1,
+                        absolute_diffuse_map_path,
+                        normal_map
+                    }
+                },
+            ),
         )(i)?;
         if mat.material_flags.contains(MaterialFlags::HAS_NORMAL_MAP) {
-            let (i, normal_map) = pair(sized_path, le_u32)(i)?;
+            let (i, normal_map) = context("normal_map", pair(sized_path, le_u32))(i)?;
             mat.normal_map = Some(normal_map);
             Ok((i, mat))
         } else {
@@ -101,14 +104,51 @@ impl Material {
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxMat(pub Box<[Material]>);
 
 impl JmxMat {
-    pub fn parse<'i>(i: &'i [u8]) -> IResult<&'i [u8], Self> {
+    pub fn parse(i: &[u8]) -> Result<Self> {
+        finish("JmxMat", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Self> {
         map(
-            preceded(tag(b"JMXVBMT 0102"), parse_objects_u32(Material::parse)),
+            preceded(
+                tag(b"JMXVBMT 0102"),
+                context("materials", parse_objects_u32(Material::parse)),
+            ),
             JmxMat,
         )(i)
     }
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn json_round_trip() {
+    let v4 = |v| Vector4 {
+        x: v,
+        y: v,
+        z: v,
+        w: 1.0,
+    };
+    let mat = JmxMat(Box::new([Material {
+        name: "body".into(),
+        diffuse: v4(1.0),
+        ambient: v4(0.5),
+        specular: v4(0.0),
+        emissive: v4(0.0),
+        specular_power: 8.0,
+        material_flags: MaterialFlags::HAS_NORMAL_MAP | MaterialFlags::UNK0,
+        diffuse_map: Path::new("body.ddj").into(),
+        unk0: 0.0,
+        unk1: 0,
+        absolute_diffuse_map_path: false,
+        normal_map: Some((Path::new("body_n.ddj").into(), 1)),
+    }]));
+    let json = serde_json::to_string(&mat).unwrap();
+    let back: JmxMat = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&back).unwrap(), json);
+    assert_eq!(back.0[0].material_flags, mat.0[0].material_flags);
+    assert_eq!(back.0[0].normal_map.as_ref().unwrap().1, 1);
+}
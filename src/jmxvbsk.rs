@@ -1,20 +1,26 @@
-use mint::{Vector3, Vector4};
+use std::collections::HashMap;
+
+use mint::{ColumnMatrix4, Vector3, Vector4};
 use nom::bytes::complete::tag;
 use nom::combinator::map;
 use nom::number::complete::{le_u32, le_u8};
-use nom::sequence::{preceded, tuple};
-use nom::IResult;
+use nom::sequence::preceded;
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
+use crate::error::{finish, IResult, Result};
+use crate::jmxvban::{JmxAnimation, KeyFrame};
+use crate::jmxvbms::JmxBMesh;
+use crate::math::{quat_normalize, Transform};
 use crate::parser_ext::multi::parse_objects_u32;
 use crate::parser_ext::number::{vector3_f32, vector4_f32};
 use crate::parser_ext::string::sized_string;
-use crate::ttr_closure;
+use crate::span::context;
+use crate::{fields, ttr_closure};
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Bone {
     pub unk: u8,
     pub name: Box<str>,
@@ -29,40 +35,63 @@ pub struct Bone {
 }
 
 impl Bone {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                le_u8,
-                sized_string,
-                sized_string,
-                vector4_f32,
-                vector3_f32,
-                vector4_f32,
-                vector3_f32,
-                vector4_f32,
-                vector3_f32,
-                parse_objects_u32(sized_string),
-            )),
-            ttr_closure! {
-                Bone {
-                    unk,
-                    name,
-                    parent_name,
-                    rotation_to_parent,
-                    translation_to_parent,
-                    rotation_to_origin,
-                    translation_to_origin,
-                    rotation_to_unknown,
-                    translation_to_unknown,
-                    children
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "Bone",
+            map(
+                fields!(
+                    unk: le_u8,
+                    name: sized_string,
+                    parent_name: sized_string,
+                    rotation_to_parent: vector4_f32,
+                    translation_to_parent: vector3_f32,
+                    rotation_to_origin: vector4_f32,
+                    translation_to_origin: vector3_f32,
+                    rotation_to_unknown: vector4_f32,
+                    translation_to_unknown: vector3_f32,
+                    children: parse_objects_u32(sized_string),
+                ),
+                ttr_closure! {
+                    Bone {
+                        unk,
+                        name,
+                        parent_name,
+                        rotation_to_parent,
+                        translation_to_parent,
+                        rotation_to_origin,
+                        translation_to_origin,
+                        rotation_to_unknown,
+                        translation_to_unknown,
+                        children
+                    }
+                },
+            ),
         )(i)
     }
+
+    pub(crate) fn local_transform(&self) -> Transform {
+        let Vector4 { x, y, z, w } = self.rotation_to_parent;
+        let Vector3 {
+            x: tx,
+            y: ty,
+            z: tz,
+        } = self.translation_to_parent;
+        Transform {
+            rotation: quat_normalize([x, y, z, w]),
+            translation: [tx, ty, tz],
+        }
+    }
+
+    /// The transform from this bone's space into its parent's, as a column-major matrix.
+    ///
+    /// The rotation is read as an `[x, y, z, w]` quaternion.
+    pub fn local_matrix(&self) -> ColumnMatrix4<f32> {
+        self.local_transform().to_matrix().into()
+    }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxSkeleton {
     pub bones: Box<[Bone]>,
     pub unk0: u32,
@@ -70,12 +99,20 @@ pub struct JmxSkeleton {
 }
 
 impl JmxSkeleton {
-    pub fn parse<'i>(i: &'i [u8]) -> IResult<&'i [u8], Self> {
+    pub fn parse(i: &[u8]) -> Result<Self> {
+        finish("JmxSkeleton", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Self> {
         preceded(
             // what about the mysterious tag(b"BSK e\0\0\0\0\x03\0\0\0")
             tag("JMXVBSK 0101"),
             map(
-                tuple((parse_objects_u32(Bone::parse), le_u32, le_u32)),
+                fields!(
+                    bones: context("bones", parse_objects_u32(Bone::parse)),
+                    unk0: le_u32,
+                    unk1: le_u32,
+                ),
                 ttr_closure! {
                     JmxSkeleton {
                         bones,
@@ -86,4 +123,244 @@ impl JmxSkeleton {
             ),
         )(i)
     }
+
+    /// The index of the bone called `name` in `bones`.
+    pub fn bone_index(&self, name: &str) -> Option<usize> {
+        self.bones.iter().position(|bone| &*bone.name == name)
+    }
+
+    pub fn bone(&self, name: &str) -> Option<&Bone> {
+        self.bones.iter().find(|bone| &*bone.name == name)
+    }
+
+    /// The index of every bone's parent, `None` for root bones and bones whose parent is missing.
+    pub fn parent_indices(&self) -> Box<[Option<usize>]> {
+        let index_of = self
+            .bones
+            .iter()
+            .enumerate()
+            .map(|(idx, bone)| (&*bone.name, idx))
+            .collect::<HashMap<_, _>>();
+        self.bones
+            .iter()
+            .enumerate()
+            .map(|(idx, bone)| {
+                index_of
+                    .get(&*bone.parent_name)
+                    .copied()
+                    .filter(|&parent| parent != idx)
+            })
+            .collect()
+    }
+
+    /// Maps the bone names of `mesh`, which its [`BoneIndexData`] indices refer to, to the
+    /// indices of the bones in this skeleton.
+    ///
+    /// [`BoneIndexData`]: crate::jmxvbms::BoneIndexData
+    pub fn mesh_bone_indices(&self, mesh: &JmxBMesh) -> Box<[Option<usize>]> {
+        match &mesh.bone_data {
+            Some((bone_names, _)) => bone_names
+                .iter()
+                .map(|name| self.bone_index(name))
+                .collect(),
+            None => Box::new([]),
+        }
+    }
+
+    pub(crate) fn world_transforms(&self) -> Box<[Transform]> {
+        let local = self
+            .bones
+            .iter()
+            .map(Bone::local_transform)
+            .collect::<Vec<_>>();
+        self.world_transforms_from(&local)
+    }
+
+    fn world_transforms_from(&self, local: &[Transform]) -> Box<[Transform]> {
+        let parents = self.parent_indices();
+        (0..self.bones.len())
+            .map(|idx| {
+                let mut world = local[idx];
+                let mut current = idx;
+                // bounded in case the parent names form a cycle
+                for _ in 0..self.bones.len() {
+                    match parents[current] {
+                        Some(parent) => {
+                            world = local[parent].mul(&world);
+                            current = parent;
+                        },
+                        None => break,
+                    }
+                }
+                world
+            })
+            .collect()
+    }
+
+    /// The local matrix of every bone, see [`Bone::local_matrix`].
+    pub fn local_matrices(&self) -> Box<[ColumnMatrix4<f32>]> {
+        self.bones.iter().map(Bone::local_matrix).collect()
+    }
+
+    /// The transform of every bone's space into model space in the bind pose.
+    pub fn world_matrices(&self) -> Box<[ColumnMatrix4<f32>]> {
+        self.world_transforms()
+            .iter()
+            .map(|world| world.to_matrix().into())
+            .collect()
+    }
+
+    /// The transform of model space into every bone's space in the bind pose.
+    pub fn inverse_bind_matrices(&self) -> Box<[ColumnMatrix4<f32>]> {
+        self.world_transforms()
+            .iter()
+            .map(|world| world.inverse().to_matrix().into())
+            .collect()
+    }
+
+    pub(crate) fn posed_world_transforms(
+        &self,
+        animation: &JmxAnimation,
+        pose: &[Option<KeyFrame>],
+    ) -> Box<[Transform]> {
+        let mut local = self
+            .bones
+            .iter()
+            .map(Bone::local_transform)
+            .collect::<Vec<_>>();
+        for (animated, key_frame) in animation.animated_bones.iter().zip(pose) {
+            if let (Some(key_frame), Some(idx)) = (key_frame, self.bone_index(&animated.name)) {
+                local[idx] = key_frame.transform();
+            }
+        }
+        self.world_transforms_from(&local)
+    }
+
+    /// The world matrices of every bone with the local transforms of the bones animated by
+    /// `animation` replaced by `pose`, as returned by [`JmxAnimation::sample`].
+    ///
+    /// Bones that aren't animated or have no key frame in `pose` keep their bind pose.
+    pub fn posed_world_matrices(
+        &self,
+        animation: &JmxAnimation,
+        pose: &[Option<KeyFrame>],
+    ) -> Box<[ColumnMatrix4<f32>]> {
+        self.posed_world_transforms(animation, pose)
+            .iter()
+            .map(|world| world.to_matrix().into())
+            .collect()
+    }
+}
+
+#[test]
+fn world_and_inverse_bind_matrices() {
+    let half = std::f32::consts::FRAC_1_SQRT_2;
+    let bone = |name: &str, parent: &str, rotation, translation| Bone {
+        unk: 0,
+        name: name.into(),
+        parent_name: parent.into(),
+        rotation_to_parent: rotation,
+        translation_to_parent: translation,
+        rotation_to_origin: Vector4 {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            w: 1.0,
+        },
+        translation_to_origin: Vector3 {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        },
+        rotation_to_unknown: Vector4 {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            w: 1.0,
+        },
+        translation_to_unknown: Vector3 {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        },
+        children: Box::new([]),
+    };
+    let skeleton = JmxSkeleton {
+        // the child comes first to make sure the order doesn't matter
+        bones: Box::new([
+            bone(
+                "child",
+                "root",
+                Vector4 {
+                    x: 0.0,
+                    y: 0.0,
+                    z: 0.0,
+                    w: 1.0,
+                },
+                Vector3 {
+                    x: 1.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+            ),
+            bone(
+                "root",
+                "",
+                Vector4 {
+                    x: 0.0,
+                    y: half,
+                    z: 0.0,
+                    w: half,
+                },
+                Vector3 {
+                    x: 0.0,
+                    y: 1.0,
+                    z: 0.0,
+                },
+            ),
+        ]),
+        unk0: 0,
+        unk1: 0,
+    };
+    assert_eq!(&*skeleton.parent_indices(), &[Some(1), None]);
+    assert_eq!(skeleton.bone_index("root"), Some(1));
+
+    // rotating 90 degrees around y turns +x into -z
+    let world = skeleton.world_matrices()[0];
+    let expected = [0.0, 1.0, -1.0];
+    let actual = [world.w.x, world.w.y, world.w.z];
+    assert!(actual
+        .iter()
+        .zip(expected)
+        .all(|(a, b)| (a - b).abs() < 1e-5));
+    // the inverse bind matrix has to move the bone's origin back to the model's origin
+    let inverse = skeleton.inverse_bind_matrices()[0];
+    let [x, y, z] = [world.w.x, world.w.y, world.w.z];
+    let back = [
+        inverse.x.x * x + inverse.y.x * y + inverse.z.x * z + inverse.w.x,
+        inverse.x.y * x + inverse.y.y * y + inverse.z.y * z + inverse.w.y,
+        inverse.x.z * x + inverse.y.z * y + inverse.z.z * z + inverse.w.z,
+    ];
+    assert!(back.iter().all(|c| c.abs() < 1e-5));
+
+    // a bone without key frames keeps its bind pose instead of being reset to the identity
+    let animation = JmxAnimation {
+        unk0: 0,
+        unk1: 0,
+        name: "still".into(),
+        duration: 0,
+        frames_per_second: 30,
+        is_continuous: false,
+        key_frame_times: Box::new([]),
+        animated_bones: Box::new([crate::jmxvban::AnimatedBone {
+            name: "child".into(),
+            keyframes: Box::new([]),
+        }]),
+    };
+    let pose = animation.sample(0.0);
+    assert!(pose[0].is_none());
+    assert_eq!(
+        skeleton.posed_world_matrices(&animation, &pose)[0],
+        skeleton.world_matrices()[0]
+    );
 }
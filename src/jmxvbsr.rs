@@ -1,22 +1,24 @@
 use mint::Vector2;
-use nom::bytes::complete::tag;
-use nom::combinator::{cond, flat_map, map};
+use nom::bytes::complete::{tag, take};
+use nom::combinator::{cond, flat_map, map, verify};
 use nom::number::complete::{le_f32, le_u32, le_u8};
-use nom::sequence::{pair, preceded, tuple};
-use nom::IResult;
+use nom::sequence::{pair, preceded};
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
 use std::path::Path;
 
+use crate::error::{finish, IResult, Result};
+use crate::parser_ext::complete::{at, take_fixed};
 use crate::parser_ext::multi::{count, parse_objects_u32};
 use crate::parser_ext::number::{vector2_f32, vector6_f32};
-use crate::parser_ext::string::{sized_path, sized_string};
-use crate::{ttr_closure, ResourceAnimationType, ResourceType};
+use crate::parser_ext::string::{sized_path, sized_string, sized_string_ref};
+use crate::span::{context, field};
+use crate::{fields, ttr_closure, ResourceAnimationType, ResourceType};
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct CollisionInfo {
     pub collision_mesh: Box<str>,
     pub collision_box0: [f32; 6],
@@ -25,48 +27,57 @@ pub struct CollisionInfo {
 }
 
 impl CollisionInfo {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                sized_string,
-                vector6_f32,
-                vector6_f32,
-                flat_map(le_u32, |val| cond(val != 0, count(le_u8, 64))),
-            )),
-            ttr_closure! {
-                CollisionInfo {
-                    collision_mesh,
-                    collision_box0,
-                    collision_box1,
-                    collision_matrix,
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "CollisionInfo",
+            map(
+                fields!(
+                    collision_mesh: sized_string,
+                    collision_box0: vector6_f32,
+                    collision_box1: vector6_f32,
+                    collision_matrix: flat_map(le_u32, |val| cond(val != 0, count(le_u8, 64))),
+                ),
+                ttr_closure! {
+                    CollisionInfo {
+                        collision_mesh,
+                        collision_box0,
+                        collision_box1,
+                        collision_matrix,
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MaterialDescriptor {
     pub id: u32,
     pub path: Box<Path>,
 }
 
 impl MaterialDescriptor {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            pair(le_u32, sized_path),
-            ttr_closure! {
-                MaterialDescriptor {
-                    id, path
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "MaterialDescriptor",
+            map(
+                fields!(
+                    id: le_u32,
+                    path: sized_path,
+                ),
+                ttr_closure! {
+                    MaterialDescriptor {
+                        id, path
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Animation {
     pub type_version: u32,
     pub type_user_define: u32,
@@ -74,40 +85,53 @@ pub struct Animation {
 }
 
 impl Animation {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((le_u32, le_u32, parse_objects_u32(sized_path))),
-            ttr_closure! {
-                Animation {
-                    type_version, type_user_define, paths
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "Animation",
+            map(
+                fields!(
+                    type_version: le_u32,
+                    type_user_define: le_u32,
+                    paths: parse_objects_u32(sized_path),
+                ),
+                ttr_closure! {
+                    Animation {
+                        type_version, type_user_define, paths
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MeshGroup {
     pub name: Box<str>,
     pub file_indices: Box<[u32]>,
 }
 
 impl MeshGroup {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            pair(sized_string, parse_objects_u32(le_u32)),
-            ttr_closure! {
-                MeshGroup {
-                    name, file_indices
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "MeshGroup",
+            map(
+                fields!(
+                    name: sized_string,
+                    file_indices: parse_objects_u32(le_u32),
+                ),
+                ttr_closure! {
+                    MeshGroup {
+                        name, file_indices
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AnimationEvent {
     pub key_time: u32,
     pub typ: u32,
@@ -116,23 +140,31 @@ pub struct AnimationEvent {
 }
 
 impl AnimationEvent {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((le_u32, le_u32, le_u32, le_u32)),
-            ttr_closure! {
-                AnimationEvent {
-                    key_time,
-                    typ,
-                    unk0,
-                    unk1
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "AnimationEvent",
+            map(
+                fields!(
+                    key_time: le_u32,
+                    typ: le_u32,
+                    unk0: le_u32,
+                    unk1: le_u32,
+                ),
+                ttr_closure! {
+                    AnimationEvent {
+                        key_time,
+                        typ,
+                        unk0,
+                        unk1
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AnimationGroupEntry {
     pub typ: ResourceAnimationType,
     pub file_index: u32,
@@ -142,47 +174,115 @@ pub struct AnimationGroupEntry {
 }
 
 impl AnimationGroupEntry {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                ResourceAnimationType::parse,
-                le_u32,
-                parse_objects_u32(AnimationEvent::parse),
-                flat_map(le_u32, |c| pair(le_f32, count(vector2_f32, c as usize))),
-            )),
-            |(typ, file_index, events, (walk_length, walk_graph))| AnimationGroupEntry {
-                typ,
-                file_index,
-                events,
-                walk_length,
-                walk_graph,
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "AnimationGroupEntry",
+            map(
+                fields!(
+                    typ: ResourceAnimationType::parse,
+                    file_index: le_u32,
+                    events: parse_objects_u32(AnimationEvent::parse),
+                    walk_graph: flat_map(le_u32, |c| {
+                        pair(field("walk_length", le_f32), count(vector2_f32, c as usize))
+                    }),
+                ),
+                |(typ, file_index, events, (walk_length, walk_graph))| AnimationGroupEntry {
+                    typ,
+                    file_index,
+                    events,
+                    walk_length,
+                    walk_graph,
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AnimationGroup {
     pub name: Box<str>,
     pub animations: Box<[AnimationGroupEntry]>,
 }
 
 impl AnimationGroup {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            pair(sized_string, parse_objects_u32(AnimationGroupEntry::parse)),
-            ttr_closure! {
-                AnimationGroup {
-                    name, animations
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "AnimationGroup",
+            map(
+                fields!(
+                    name: sized_string,
+                    animations: parse_objects_u32(AnimationGroupEntry::parse),
+                ),
+                ttr_closure! {
+                    AnimationGroup {
+                        name, animations
+                    }
+                },
+            ),
         )(i)
     }
 }
 
+/// The longest string [`ModPalette::parse`] considers to be a path.
+const MAX_PATH_LEN: usize = 260;
+
+/// The section [`JmxResHeader::mod_palette_offset`] points to, which drives the colour and
+/// texture variations of equipment.
+///
+/// The layout of its entries isn't known yet, so the section is kept as is and the paths it
+/// references are picked out of it by looking for sized strings that look like file paths.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ModPalette {
+    /// The bytes from the section's offset up to the next section or the end of the file.
+    pub data: Box<[u8]>,
+    /// The paths found in `data` with the offsets of their length prefixes.
+    pub paths: Box<[(u32, Box<Path>)]>,
+}
+
+impl ModPalette {
+    fn parse(len: usize) -> impl FnMut(&[u8]) -> IResult<&[u8], Self> {
+        move |i| {
+            map(take(len), |data: &[u8]| ModPalette {
+                data: data.into(),
+                paths: Self::find_paths(data),
+            })(i)
+        }
+    }
+
+    fn find_paths(data: &[u8]) -> Box<[(u32, Box<Path>)]> {
+        let mut paths = Vec::new();
+        let mut offset = 0;
+        while offset + 4 < data.len() {
+            let len = u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap()) as usize;
+            if (1..=MAX_PATH_LEN).contains(&len) {
+                if let Ok((rest, path)) = sized_string_ref(&data[offset..]) {
+                    if looks_like_path(&path) {
+                        paths.push((offset as u32, Path::new(&*path).into()));
+                        offset = data.len() - rest.len();
+                        continue;
+                    }
+                }
+            }
+            offset += 1;
+        }
+        paths.into_boxed_slice()
+    }
+}
+
+/// Whether `s` is free of control characters and undecodable bytes and ends in a file extension.
+fn looks_like_path(s: &str) -> bool {
+    let extension = s.rsplit_once('.').map(|(_, extension)| extension);
+    !s.contains(|c: char| c.is_control() || c == char::REPLACEMENT_CHARACTER)
+        && extension.is_some_and(|extension| {
+            (2..=4).contains(&extension.len())
+                && extension.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxRes {
     pub header: JmxResHeader,
     pub bounding_box: CollisionInfo,
@@ -193,25 +293,64 @@ pub struct JmxRes {
     pub skeleton_paths: Box<[(Box<Path>, Box<[u8]>)]>,
     pub mesh_groups: Box<[MeshGroup]>,
     pub animation_groups: Box<[AnimationGroup]>,
+    /// `None` if the header has no mod palette offset.
+    pub mod_palette: Option<ModPalette>,
 }
 
 impl JmxRes {
-    pub fn parse<'i>(i: &'i [u8]) -> IResult<&'i [u8], Self> {
-        let (_, header) = nom::error::context("resource header", JmxResHeader::parse)(i)?;
-        let (_, bounding_box) = CollisionInfo::parse(&i[header.collision_offset as usize..])?;
-        let (_, material_sets) =
-            parse_objects_u32(MaterialDescriptor::parse)(&i[header.material_offset as usize..])?;
-        let (_, mesh_paths) = parse_objects_u32(pair(sized_path, cond(header.unk0 == 1, le_u32)))(
-            &i[header.mesh_offset as usize..],
-        )?;
-        let (_, animation) = Animation::parse(&i[header.animation_offset as usize..])?;
-        let (_, skeleton_paths) = parse_objects_u32(pair(sized_path, parse_objects_u32(le_u8)))(
-            &i[header.skeleton_offset as usize..],
-        )?;
-        let (_, mesh_groups) =
-            parse_objects_u32(MeshGroup::parse)(&i[header.prim_mesh_group_offset as usize..])?;
-        let (_, animation_groups) =
-            parse_objects_u32(AnimationGroup::parse)(&i[header.prim_ani_group_offset as usize..])?;
+    pub fn parse(i: &[u8]) -> Result<Self> {
+        finish("JmxRes", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Self> {
+        let (_, header) = JmxResHeader::parse(i)?;
+        let (_, bounding_box) = at(header.collision_offset, CollisionInfo::parse)(i)?;
+        let (_, material_sets) = context(
+            "material_sets",
+            at(
+                header.material_offset,
+                parse_objects_u32(MaterialDescriptor::parse),
+            ),
+        )(i)?;
+        let (_, mesh_paths) = context(
+            "mesh_paths",
+            at(
+                header.mesh_offset,
+                parse_objects_u32(pair(sized_path, cond(header.unk0 == 1, le_u32))),
+            ),
+        )(i)?;
+        let (_, animation) = at(header.animation_offset, Animation::parse)(i)?;
+        let (_, skeleton_paths) = context(
+            "skeleton_paths",
+            at(
+                header.skeleton_offset,
+                parse_objects_u32(pair(sized_path, parse_objects_u32(le_u8))),
+            ),
+        )(i)?;
+        let (_, mesh_groups) = context(
+            "mesh_groups",
+            at(
+                header.prim_mesh_group_offset,
+                parse_objects_u32(MeshGroup::parse),
+            ),
+        )(i)?;
+        let (_, animation_groups) = context(
+            "animation_groups",
+            at(
+                header.prim_ani_group_offset,
+                parse_objects_u32(AnimationGroup::parse),
+            ),
+        )(i)?;
+        let (_, mod_palette) = context(
+            "mod_palette",
+            cond(
+                header.mod_palette_offset != 0,
+                at(
+                    header.mod_palette_offset,
+                    ModPalette::parse(header.mod_palette_len(i.len())),
+                ),
+            ),
+        )(i)?;
 
         Ok((
             &[],
@@ -224,14 +363,20 @@ impl JmxRes {
                 skeleton_paths,
                 mesh_groups,
                 animation_groups,
+                mod_palette,
             },
         ))
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxResHeader {
+    /// Either `0107`, `0108` or `0109`.
+    ///
+    /// How the older versions differ from `0109` isn't known yet, they are parsed with the same
+    /// layout.
+    pub version: [u8; 4],
     pub material_offset: u32,
     pub mesh_offset: u32,
     pub skeleton_offset: u32,
@@ -254,52 +399,121 @@ pub struct JmxResHeader {
 }
 
 impl JmxResHeader {
-    pub fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        preceded(
-            // FIXME:  107 and 108 have differences from 109
-            tag("JMXVRES 0109"),
-            map(
-                tuple((
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    ResourceType::parse,
-                    sized_string,
-                    le_u32,
-                    le_u32,
-                )),
-                ttr_closure! {
-                    JmxResHeader {
-                        material_offset,
-                        mesh_offset,
-                        skeleton_offset,
-                        animation_offset,
-                        prim_mesh_group_offset,
-                        prim_ani_group_offset,
-                        mod_palette_offset,
-                        collision_offset,
-                        unk0,
-                        unk1,
-                        unk2,
-                        unk3,
-                        unk4,
-                        res_type,
-                        name,
-                        unk5,
-                        unk6
-                    }
-                },
+    /// The length of the mod palette section, assuming it ends where the next section or the file
+    /// of `file_len` bytes does.
+    fn mod_palette_len(&self, file_len: usize) -> usize {
+        let start = self.mod_palette_offset as usize;
+        let end = [
+            self.material_offset,
+            self.mesh_offset,
+            self.skeleton_offset,
+            self.animation_offset,
+            self.prim_mesh_group_offset,
+            self.prim_ani_group_offset,
+            self.collision_offset,
+        ]
+        .into_iter()
+        .map(|offset| offset as usize)
+        .filter(|&offset| offset > start)
+        .min()
+        .unwrap_or(file_len)
+        .min(file_len);
+        end.saturating_sub(start)
+    }
+
+    pub(crate) fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "JmxResHeader",
+            preceded(
+                tag(b"JMXVRES "),
+                map(
+                    fields!(
+                        version: verify(take_fixed::<4>, |version: &[u8; 4]| {
+                            matches!(version, b"0107" | b"0108" | b"0109")
+                        }),
+                        material_offset: le_u32,
+                        mesh_offset: le_u32,
+                        skeleton_offset: le_u32,
+                        animation_offset: le_u32,
+                        prim_mesh_group_offset: le_u32,
+                        prim_ani_group_offset: le_u32,
+                        mod_palette_offset: le_u32,
+                        collision_offset: le_u32,
+                        unk0: le_u32,
+                        unk1: le_u32,
+                        unk2: le_u32,
+                        unk3: le_u32,
+                        unk4: le_u32,
+                        res_type: ResourceType::parse,
+                        name: sized_string,
+                        unk5: le_u32,
+                        unk6: le_u32,
+                    ),
+                    ttr_closure! {
+                        JmxResHeader {
+                            version,
+                            material_offset,
+                            mesh_offset,
+                            skeleton_offset,
+                            animation_offset,
+                            prim_mesh_group_offset,
+                            prim_ani_group_offset,
+                            mod_palette_offset,
+                            collision_offset,
+                            unk0,
+                            unk1,
+                            unk2,
+                            unk3,
+                            unk4,
+                            res_type,
+                            name,
+                            unk5,
+                            unk6
+                        }
+                    },
+                ),
             ),
         )(i)
     }
 }
+
+#[test]
+fn parse_header_versions() {
+    let header = |version: &[u8]| {
+        let mut data = [b"JMXVRES ", version].concat();
+        for v in [0u32; 13] {
+            data.extend(v.to_le_bytes());
+        }
+        data.extend(0x20000u32.to_le_bytes());
+        data.extend(4u32.to_le_bytes());
+        data.extend(b"name");
+        data.extend([0; 8]);
+        data
+    };
+    for version in [b"0107", b"0108", b"0109"] {
+        let (_, parsed) = JmxResHeader::parse(&header(version)).unwrap();
+        assert_eq!(&parsed.version, version);
+        assert_eq!(&*parsed.name, "name");
+    }
+    assert!(JmxResHeader::parse(&header(b"0110")).is_err());
+}
+
+#[test]
+fn mod_palette_paths() {
+    let mut data = vec![2, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF];
+    data.extend(14u32.to_le_bytes());
+    data.extend(b"item\\cloth.ddj");
+    data.extend(1.5f32.to_le_bytes());
+    data.extend(4u32.to_le_bytes());
+    data.extend(b"name");
+    data.extend(9u32.to_le_bytes());
+    data.extend(b"res\\a.bmt");
+    let (rest, palette) = ModPalette::parse(data.len() - 4)(&data).unwrap();
+    assert_eq!(rest.len(), 4);
+    assert_eq!(palette.data.len(), data.len() - 4);
+    // the last path is cut off by the section's end
+    assert_eq!(
+        palette.paths,
+        vec![(8, Path::new("item\\cloth.ddj").into())].into_boxed_slice()
+    );
+}
@@ -1,20 +1,22 @@
 use std::path::Path;
 
 use nom::bytes::complete::tag;
-use nom::combinator::{map, map_res};
+use nom::combinator::map;
 use nom::number::complete::le_u32;
-use nom::sequence::{preceded, tuple};
-use nom::IResult;
+use nom::sequence::preceded;
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
+use crate::error::{finish, IResult, Result};
+use crate::parser_ext::complete::at;
 use crate::parser_ext::multi::parse_objects_u32;
 use crate::parser_ext::string::{sized_path, sized_string};
-use crate::{ttr_closure, ResourceType};
+use crate::span::context;
+use crate::{fields, ttr_closure, ResourceType};
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxCompound {
     pub header: JmxCompoundHeader,
     pub collision_resource_path: Box<Path>,
@@ -22,12 +24,21 @@ pub struct JmxCompound {
 }
 
 impl JmxCompound {
-    pub fn parse<'i>(i: &'i [u8]) -> IResult<&'i [u8], Self> {
+    pub fn parse(i: &[u8]) -> Result<Self> {
+        finish("JmxCompound", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Self> {
         let (_, header) = JmxCompoundHeader::parse(i)?;
 
-        let (_, collision_resource_path) = sized_path(&i[header.collision_resources as usize..])?;
-        let (_, resource_paths) =
-            parse_objects_u32(sized_path)(&i[header.resource_list as usize..])?;
+        let (_, collision_resource_path) = context(
+            "collision_resource_path",
+            at(header.collision_resources, sized_path),
+        )(i)?;
+        let (_, resource_paths) = context(
+            "resource_paths",
+            at(header.resource_list, parse_objects_u32(sized_path)),
+        )(i)?;
 
         Ok((
             &[],
@@ -41,7 +52,7 @@ impl JmxCompound {
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxCompoundHeader {
     pub collision_resources: u32,
     pub resource_list: u32,
@@ -57,38 +68,41 @@ pub struct JmxCompoundHeader {
 }
 
 impl JmxCompoundHeader {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        preceded(
-            tag(b"JMXVCPD 0101"),
-            map(
-                tuple((
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    map_res(le_u32, TryFrom::try_from),
-                    sized_string,
-                    le_u32,
-                    le_u32,
-                )),
-                ttr_closure! {
-                    JmxCompoundHeader {
-                        collision_resources,
-                        resource_list,
-                        unk0,
-                        unk1,
-                        unk2,
-                        unk3,
-                        unk4,
-                        typ,
-                        name,
-                        unk5,
-                        unk6
-                    }
-                },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "JmxCompoundHeader",
+            preceded(
+                tag(b"JMXVCPD 0101"),
+                map(
+                    fields!(
+                        collision_resources: le_u32,
+                        resource_list: le_u32,
+                        unk0: le_u32,
+                        unk1: le_u32,
+                        unk2: le_u32,
+                        unk3: le_u32,
+                        unk4: le_u32,
+                        typ: ResourceType::parse,
+                        name: sized_string,
+                        unk5: le_u32,
+                        unk6: le_u32,
+                    ),
+                    ttr_closure! {
+                        JmxCompoundHeader {
+                            collision_resources,
+                            resource_list,
+                            unk0,
+                            unk1,
+                            unk2,
+                            unk3,
+                            unk4,
+                            typ,
+                            name,
+                            unk5,
+                            unk6
+                        }
+                    },
+                ),
             ),
         )(i)
     }
@@ -1,28 +1,40 @@
+use std::io::{self, Write};
+
 use nom::bytes::complete::tag;
-use nom::combinator::{flat_map, map};
+use nom::combinator::{flat_map, map, verify};
 use nom::multi::count;
 use nom::number::complete::{le_u32, le_u8};
-use nom::sequence::{pair, preceded};
+use nom::sequence::preceded;
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
-use crate::ttr_closure;
+use crate::dds::{self, DdsHeader, EncodeFormat, RgbaImage};
+use crate::error::{finish, IResult, Result};
+use crate::writer_ext::number::write_u32;
+use crate::{fields, ttr_closure};
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxTexture {
     pub header_len: u32,
     pub data: Vec<u8>,
 }
 
 impl JmxTexture {
-    pub fn parse<'i>(i: &'i [u8]) -> nom::IResult<&'i [u8], Self> {
+    pub fn parse(i: &[u8]) -> Result<Self> {
+        finish("JmxTexture", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Self> {
         map(
             preceded(
                 tag(b"JMXVDDJ 1000"),
-                flat_map(le_u32, |texture_size| {
-                    pair(le_u32, count(le_u8, texture_size as usize - 8))
+                flat_map(verify(le_u32, |&size| size >= 8), |texture_size| {
+                    fields!(
+                        header_len: le_u32,
+                        data: count(le_u8, texture_size as usize - 8),
+                    )
                 }),
             ),
             ttr_closure! {
@@ -33,4 +45,75 @@ impl JmxTexture {
             },
         )(i)
     }
+
+    /// Parses the header of the DDS file stored in `data`.
+    pub fn dds_header(&self) -> Result<DdsHeader> {
+        DdsHeader::parse(&self.data)
+    }
+
+    /// Decodes all mip levels of the texture to RGBA8, largest first.
+    pub fn decode(&self) -> Result<Box<[RgbaImage]>> {
+        dds::decode(&self.data)
+    }
+
+    /// Compresses `image` into a new texture, optionally with a generated mip chain.
+    ///
+    /// `header_len` is written as is, take it from a texture of the client the file is meant for.
+    /// [`JmxTexture::replace_image`] keeps the value of a parsed texture instead.
+    pub fn encode(
+        image: &RgbaImage,
+        format: EncodeFormat,
+        generate_mips: bool,
+        header_len: u32,
+    ) -> Self {
+        let mut texture = JmxTexture {
+            header_len,
+            data: Vec::new(),
+        };
+        texture.replace_image(image, format, generate_mips);
+        texture
+    }
+
+    /// Replaces the texture's data with `image` compressed like [`JmxTexture::encode`] does,
+    /// keeping `header_len`.
+    pub fn replace_image(&mut self, image: &RgbaImage, format: EncodeFormat, generate_mips: bool) {
+        self.data = if generate_mips {
+            dds::encode(&image.mip_chain(), format)
+        } else {
+            dds::encode(std::slice::from_ref(image), format)
+        };
+    }
+
+    pub fn write(&self, w: &mut impl Write) -> io::Result<()> {
+        w.write_all(b"JMXVDDJ 1000")?;
+        write_u32(w, self.data.len() as u32 + 8)?;
+        write_u32(w, self.header_len)?;
+        w.write_all(&self.data)
+    }
+}
+
+#[test]
+fn write_round_trip() {
+    let image = RgbaImage {
+        width: 4,
+        height: 4,
+        data: [255, 0, 0, 255].repeat(16).into_boxed_slice(),
+    };
+    let texture = JmxTexture::encode(&image, EncodeFormat::Dxt1, true, 3);
+    let mut file = Vec::new();
+    texture.write(&mut file).unwrap();
+    let parsed = JmxTexture::parse(&file).unwrap();
+    assert_eq!(parsed.header_len, 3);
+    assert_eq!(parsed.data, texture.data);
+    let levels = parsed.decode().unwrap();
+    assert_eq!(levels.len(), 3);
+    assert_eq!(levels[0], image);
+
+    let mut replaced = JmxTexture {
+        header_len: 7,
+        ..parsed
+    };
+    replaced.replace_image(&image, EncodeFormat::Dxt5, false);
+    assert_eq!(replaced.header_len, 7);
+    assert_eq!(replaced.decode().unwrap().len(), 1);
 }
@@ -3,20 +3,23 @@ use std::path::Path;
 use mint::Vector3;
 use nom::bytes::complete::tag;
 use nom::combinator::{cond, flat_map, map};
-use nom::number::complete::{le_f32, le_u16, le_u32, le_u8};
-use nom::sequence::{pair, preceded, tuple};
-use nom::IResult;
+use nom::number::complete::{le_f32, le_u32, le_u8};
+use nom::sequence::{pair, preceded};
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
+use crate::error::{finish, IResult, Result};
+use crate::parser_ext::complete::at;
 use crate::parser_ext::multi::{count, parse_objects_u32};
 use crate::parser_ext::number::{vector3_f32, vector6_f32};
 use crate::parser_ext::string::{sized_path, sized_string};
-use crate::ttr_closure;
+use crate::region::RegionId;
+use crate::span::{context, field};
+use crate::{fields, ttr_closure};
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RoomObjectPoint {
     pub name: Box<str>,
     pub position: Vector3<f32>,
@@ -29,36 +32,39 @@ pub struct RoomObjectPoint {
 }
 
 impl RoomObjectPoint {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                sized_string,
-                vector3_f32,
-                vector3_f32,
-                vector3_f32,
-                vector3_f32,
-                le_f32,
-                le_f32,
-                le_f32,
-            )),
-            ttr_closure! {
-                RoomObjectPoint {
-                    name,
-                    position,
-                    rotation,
-                    size,
-                    rotation2,
-                    unk0,
-                    unk2,
-                    unk1
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "RoomObjectPoint",
+            map(
+                fields!(
+                    name: sized_string,
+                    position: vector3_f32,
+                    rotation: vector3_f32,
+                    size: vector3_f32,
+                    rotation2: vector3_f32,
+                    unk0: le_f32,
+                    unk2: le_f32,
+                    unk1: le_f32,
+                ),
+                ttr_closure! {
+                    RoomObjectPoint {
+                        name,
+                        position,
+                        rotation,
+                        size,
+                        rotation2,
+                        unk0,
+                        unk2,
+                        unk1
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RoomObjectEntry {
     pub name: Box<str>,
     pub path: Box<Path>,
@@ -72,38 +78,41 @@ pub struct RoomObjectEntry {
 }
 
 impl RoomObjectEntry {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                sized_string,
-                sized_path,
-                vector3_f32,
-                vector3_f32,
-                vector3_f32,
-                // FIXME:
-                flat_map(le_u32, |f| map(cond(f == 0x04, le_u32), move |w| (f, w))),
-                le_u32,
-                le_f32, // FIXME: <- this is what should be read for flag 0x04
-            )),
-            |(name, path, position, rotation, scale, (flag, water_extra), id, unk0)| {
-                RoomObjectEntry {
-                    name,
-                    path,
-                    position,
-                    rotation,
-                    scale,
-                    flag,
-                    water_extra,
-                    id,
-                    unk0,
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "RoomObjectEntry",
+            map(
+                fields!(
+                    name: sized_string,
+                    path: sized_path,
+                    position: vector3_f32,
+                    rotation: vector3_f32,
+                    scale: vector3_f32,
+                    // FIXME:
+                    flag: flat_map(le_u32, |f| map(cond(f == 0x04, le_u32), move |w| (f, w))),
+                    id: le_u32,
+                    unk0: le_f32, // FIXME: <- this is what should be read for flag 0x04
+                ),
+                |(name, path, position, rotation, scale, (flag, water_extra), id, unk0)| {
+                    RoomObjectEntry {
+                        name,
+                        path,
+                        position,
+                        rotation,
+                        scale,
+                        flag,
+                        water_extra,
+                        id,
+                        unk0,
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RoomObjectExtraA {
     pub unk0: f32,
     pub unk1: f32,
@@ -112,23 +121,31 @@ pub struct RoomObjectExtraA {
 }
 
 impl RoomObjectExtraA {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((le_f32, le_f32, le_f32, le_f32)),
-            ttr_closure! {
-                RoomObjectExtraA {
-                    unk0,
-                    unk1,
-                    unk2,
-                    unk3
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "RoomObjectExtraA",
+            map(
+                fields!(
+                    unk0: le_f32,
+                    unk1: le_f32,
+                    unk2: le_f32,
+                    unk3: le_f32,
+                ),
+                ttr_closure! {
+                    RoomObjectExtraA {
+                        unk0,
+                        unk1,
+                        unk2,
+                        unk3
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RoomObjectExtraB {
     pub unk0: f32,
     pub unk1: f32,
@@ -140,18 +157,29 @@ pub struct RoomObjectExtraB {
 }
 
 impl RoomObjectExtraB {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((le_f32, le_f32, le_f32, le_f32, le_f32, le_f32, le_f32)),
-            ttr_closure! {
-                RoomObjectExtraB { unk0, unk1, unk2, unk3, unk4, unk5, unk6 }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "RoomObjectExtraB",
+            map(
+                fields!(
+                    unk0: le_f32,
+                    unk1: le_f32,
+                    unk2: le_f32,
+                    unk3: le_f32,
+                    unk4: le_f32,
+                    unk5: le_f32,
+                    unk6: le_f32,
+                ),
+                ttr_closure! {
+                    RoomObjectExtraB { unk0, unk1, unk2, unk3, unk4, unk5, unk6 }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RoomObject {
     pub path: Box<Path>,
     pub name: Box<str>,
@@ -178,85 +206,91 @@ pub struct RoomObject {
 }
 
 impl RoomObject {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                sized_path,
-                sized_string,
-                le_u32,
-                vector3_f32,
-                le_f32,
-                le_f32,
-                vector6_f32,
-                le_u32,
-                le_f32,
-                le_f32,
-                le_f32,
-                le_f32,
-                flat_map(le_u8, |val| cond(val == 0x01, RoomObjectExtraA::parse)),
-                flat_map(le_u8, |val| cond(val == 0x02, RoomObjectExtraB::parse)),
-                sized_string,
-                le_u32,
-                le_u32,
-                parse_objects_u32(le_u32),
-                parse_objects_u32(le_u32),
-                flat_map(le_u32, |c| {
-                    pair(le_u32, count(RoomObjectEntry::parse, c as usize))
-                }),
-                parse_objects_u32(RoomObjectPoint::parse),
-            )),
-            |(
-                path,
-                name,
-                unk0,
-                position,
-                yaw,
-                pitch,
-                aabb,
-                unk1,
-                fog_color,
-                fog_near_plane,
-                fog_far_plane,
-                fog_intensity,
-                extra_a,
-                extra_b,
-                unk6,
-                room_index,
-                floor_index,
-                connected_objects,
-                indirect_connected_objects,
-                (unk7, entries),
-                points,
-            )| RoomObject {
-                path,
-                name,
-                unk0,
-                position,
-                yaw,
-                is_entrance: pitch,
-                aabb,
-                unk1,
-                fog_color,
-                fog_near_plane,
-                fog_far_plane,
-                fog_intensity,
-                extra_a,
-                extra_b,
-                unk6,
-                room_index,
-                floor_index,
-                connected_objects,
-                indirect_connected_objects,
-                unk7,
-                entries,
-                points,
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "RoomObject",
+            map(
+                fields!(
+                    path: sized_path,
+                    name: sized_string,
+                    unk0: le_u32,
+                    position: vector3_f32,
+                    yaw: le_f32,
+                    is_entrance: le_f32,
+                    aabb: vector6_f32,
+                    unk1: le_u32,
+                    fog_color: le_f32,
+                    fog_near_plane: le_f32,
+                    fog_far_plane: le_f32,
+                    fog_intensity: le_f32,
+                    extra_a: flat_map(le_u8, |val| cond(val == 0x01, RoomObjectExtraA::parse)),
+                    extra_b: flat_map(le_u8, |val| cond(val == 0x02, RoomObjectExtraB::parse)),
+                    unk6: sized_string,
+                    room_index: le_u32,
+                    floor_index: le_u32,
+                    connected_objects: parse_objects_u32(le_u32),
+                    indirect_connected_objects: parse_objects_u32(le_u32),
+                    entries: flat_map(le_u32, |c| {
+                        pair(
+                            field("unk7", le_u32),
+                            count(RoomObjectEntry::parse, c as usize),
+                        )
+                    }),
+                    points: parse_objects_u32(RoomObjectPoint::parse),
+                ),
+                |(
+                    path,
+                    name,
+                    unk0,
+                    position,
+                    yaw,
+                    pitch,
+                    aabb,
+                    unk1,
+                    fog_color,
+                    fog_near_plane,
+                    fog_far_plane,
+                    fog_intensity,
+                    extra_a,
+                    extra_b,
+                    unk6,
+                    room_index,
+                    floor_index,
+                    connected_objects,
+                    indirect_connected_objects,
+                    (unk7, entries),
+                    points,
+                )| RoomObject {
+                    path,
+                    name,
+                    unk0,
+                    position,
+                    yaw,
+                    is_entrance: pitch,
+                    aabb,
+                    unk1,
+                    fog_color,
+                    fog_near_plane,
+                    fog_far_plane,
+                    fog_intensity,
+                    extra_a,
+                    extra_b,
+                    unk6,
+                    room_index,
+                    floor_index,
+                    connected_objects,
+                    indirect_connected_objects,
+                    unk7,
+                    entries,
+                    points,
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ObjectGroup {
     pub name: Box<str>,
     pub flag: u32,
@@ -264,36 +298,46 @@ pub struct ObjectGroup {
 }
 
 impl ObjectGroup {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((sized_string, le_u32, parse_objects_u32(le_u32))),
-            ttr_closure! {
-                ObjectGroup {
-                    name, flag, object_indices
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "ObjectGroup",
+            map(
+                fields!(
+                    name: sized_string,
+                    flag: le_u32,
+                    object_indices: parse_objects_u32(le_u32),
+                ),
+                ttr_closure! {
+                    ObjectGroup {
+                        name, flag, object_indices
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Link {
     pub id: u32,
     pub connections: Box<[u32]>,
 }
 
 impl Link {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            pair(le_u32, parse_objects_u32(le_u32)),
-            |(id, connections)| Link { id, connections },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "Link",
+            map(
+                fields!(id: le_u32, connections: parse_objects_u32(le_u32)),
+                |(id, connections)| Link { id, connections },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Links {
     pub unk0: u32,
     pub unk1: u32,
@@ -302,20 +346,28 @@ pub struct Links {
 }
 
 impl Links {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((le_u32, le_u32, le_u32, parse_objects_u32(Link::parse))),
-            ttr_closure! {
-                Links {
-                    unk0, unk1, unk2, links
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "Links",
+            map(
+                fields!(
+                    unk0: le_u32,
+                    unk1: le_u32,
+                    unk2: le_u32,
+                    links: parse_objects_u32(Link::parse),
+                ),
+                ttr_closure! {
+                    Links {
+                        unk0, unk1, unk2, links
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxDungeon {
     pub header: JmxDungeonHeader,
     pub aabb: [f32; 6],
@@ -329,21 +381,39 @@ pub struct JmxDungeon {
 }
 
 impl JmxDungeon {
-    pub fn parse<'i>(i: &'i [u8]) -> IResult<&'i [u8], Self> {
+    pub fn parse(i: &[u8]) -> Result<Self> {
+        finish("JmxDungeon", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Self> {
         let (_, header) = JmxDungeonHeader::parse(i)?;
-        let (_, (aabb, oobb)) =
-            pair(vector6_f32, vector6_f32)(&i[header.bounding_boxes as usize..])?;
-        let (_, room_objects) =
-            parse_objects_u32(RoomObject::parse)(&i[header.room_objects as usize..])?;
-        let (_, links) = Links::parse(&i[header.links as usize..])?;
-        let (_, object_connections) =
-            parse_objects_u32(parse_objects_u32(le_u32))(&i[header.object_connections as usize..])?;
-        let (_, (room_names, floor_names)) = pair(
-            parse_objects_u32(sized_string),
-            parse_objects_u32(sized_string),
-        )(&i[header.index_names as usize..])?;
-        let (_, object_groups) =
-            parse_objects_u32(ObjectGroup::parse)(&i[header.object_groups as usize..])?;
+        let (_, (aabb, oobb)) = at(
+            header.bounding_boxes,
+            pair(context("aabb", vector6_f32), context("oobb", vector6_f32)),
+        )(i)?;
+        let (_, room_objects) = context(
+            "room_objects",
+            at(header.room_objects, parse_objects_u32(RoomObject::parse)),
+        )(i)?;
+        let (_, links) = at(header.links, Links::parse)(i)?;
+        let (_, object_connections) = context(
+            "object_connections",
+            at(
+                header.object_connections,
+                parse_objects_u32(parse_objects_u32(le_u32)),
+            ),
+        )(i)?;
+        let (_, (room_names, floor_names)) = at(
+            header.index_names,
+            pair(
+                context("room_names", parse_objects_u32(sized_string)),
+                context("floor_names", parse_objects_u32(sized_string)),
+            ),
+        )(i)?;
+        let (_, object_groups) = context(
+            "object_groups",
+            at(header.object_groups, parse_objects_u32(ObjectGroup::parse)),
+        )(i)?;
 
         Ok((
             &[],
@@ -363,7 +433,7 @@ impl JmxDungeon {
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxDungeonHeader {
     pub room_objects: u32,
     pub object_connections: u32,
@@ -377,46 +447,49 @@ pub struct JmxDungeonHeader {
     pub dungeon_name: Box<str>,
     pub unk4: u32,
     pub unk5: u32,
-    pub region_id: u16,
+    pub region_id: RegionId,
 }
 
 impl JmxDungeonHeader {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        preceded(
-            tag(b"JMXVDOF 0101"),
-            map(
-                tuple((
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    le_u32,
-                    sized_string,
-                    le_u32,
-                    le_u32,
-                    le_u16,
-                )),
-                ttr_closure! {
-                    JmxDungeonHeader {
-                        room_objects,
-                        object_connections,
-                        links,
-                        object_groups,
-                        index_names,
-                        unk0,
-                        unk1,
-                        bounding_boxes,
-                        ty,
-                        dungeon_name,
-                        unk4,
-                        unk5,
-                        region_id
-                    }
-                },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "JmxDungeonHeader",
+            preceded(
+                tag(b"JMXVDOF 0101"),
+                map(
+                    fields!(
+                        room_objects: le_u32,
+                        object_connections: le_u32,
+                        links: le_u32,
+                        object_groups: le_u32,
+                        index_names: le_u32,
+                        unk0: le_u32,
+                        unk1: le_u32,
+                        bounding_boxes: le_u32,
+                        ty: le_u32,
+                        dungeon_name: sized_string,
+                        unk4: le_u32,
+                        unk5: le_u32,
+                        region_id: RegionId::parse,
+                    ),
+                    ttr_closure! {
+                        JmxDungeonHeader {
+                            room_objects,
+                            object_connections,
+                            links,
+                            object_groups,
+                            index_names,
+                            unk0,
+                            unk1,
+                            bounding_boxes,
+                            ty,
+                            dungeon_name,
+                            unk4,
+                            unk5,
+                            region_id
+                        }
+                    },
+                ),
             ),
         )(i)
     }
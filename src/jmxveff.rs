@@ -3,22 +3,28 @@ use nom::bytes::complete::tag;
 use nom::combinator::{cond, flat_map, map};
 use nom::error::{make_error, ErrorKind};
 use nom::number::complete::{le_f32, le_i32, le_u32, le_u8};
-use nom::sequence::{pair, tuple};
-use nom::IResult;
+use nom::sequence::pair;
 
-use crate::parser_ext::complete::take_fixed;
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use crate::error::{finish, IResult, Result};
+use crate::parser_ext::complete::{at, take_fixed};
 use crate::parser_ext::multi::parse_objects_u32;
 use crate::parser_ext::number::{matrix4x4, vector3_f32, vector4_f32};
 use crate::parser_ext::string::{sized_string, sized_string_ref};
-use crate::ttr_closure;
+use crate::span::{context, field};
+use crate::{fields, ttr_closure};
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxvEff {
     pub header: JmxvEffHeader,
     pub root: EFStoredObject,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxvEffHeader {
     pub version: [u8; 4],
     pub v12_unk0: u32,
@@ -28,6 +34,7 @@ pub struct JmxvEffHeader {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EFStoredObject {
     pub name: Box<str>,
     pub controllers: Box<[EFController]>,
@@ -62,6 +69,7 @@ pub struct EFStoredObject {
 
 /// I wonder what these are for? 🤔
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum EFController {
     // Basic
     NormalTimeLife,
@@ -99,6 +107,7 @@ pub enum EFController {
 
 /// Specifies whether the particles produced by the system should be locked towards the camera.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ViewMode {
     /// No locking
     None,
@@ -112,6 +121,7 @@ pub enum ViewMode {
 
 /// What kind of mesh does the particle use
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum RenderShape {
     /// Trail?
     LinkDPipe,
@@ -128,12 +138,15 @@ pub enum RenderShape {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EESourceList(pub Box<[Option<EESourceData>]>);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EESource(pub Option<EESourceData>);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EESourceData {
     pub command: EECommand,
     pub subtype: u8,
@@ -144,6 +157,7 @@ pub struct EESourceData {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum EECommand {
     // EECFuncP0
     NeverExtinct,
@@ -183,6 +197,7 @@ pub enum EECommand {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EEResource {
     pub two_sided: bool,
     pub src_blend: u32,
@@ -198,18 +213,22 @@ pub struct EEResource {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BSAnimation(Box<[Box<str>]>);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EEGlobalData {
     pub unk0: u32,
     pub parameters: Box<[EEParameter]>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EEProgram(pub EESourceList);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EFStaticEmit {
     pub min: i32,
     pub max: i32,
@@ -219,6 +238,7 @@ pub struct EFStaticEmit {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum EEParameter {
     Float(f32),
     Vector(Vector3<f32>),
@@ -239,30 +259,39 @@ pub enum EEParameter {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AngleVector1(pub Vector3<f32>, pub Vector3<f32>);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct AxisVector4(pub Vector4<f32>, pub RowMatrix4<f32>);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FrameTextureSlide(pub Vector3<f32>, pub Box<[Vector4<f32>]>);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct RotVector(pub Vector3<f32>, pub RowMatrix4<f32>);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FrameBANPosition(pub f32, pub Box<[Vector3<f32>]>);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FrameBANRotation(pub f32, pub Box<[RowMatrix4<f32>]>);
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FrameDiffuse(pub Box<[Color]>);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FrameScale(pub Box<[Vector3<f32>]>);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EEBlend<T> {
     pub begin: f32,
     pub end: f32,
@@ -271,12 +300,20 @@ pub struct EEBlend<T> {
 
 // ARGB32
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Color(pub u32);
 
 impl JmxvEff {
-    pub fn parse(i: &[u8]) -> IResult<&[u8], JmxvEff> {
+    pub fn parse(i: &[u8]) -> Result<JmxvEff> {
+        finish("JmxvEff", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], JmxvEff> {
         map(
-            pair(JmxvEffHeader::parse, EFStoredObject::parse),
+            fields!(
+                header: context("JmxvEffHeader", JmxvEffHeader::parse),
+                root: EFStoredObject::parse,
+            ),
             ttr_closure!(JmxvEff { header, root }),
         )(i)
     }
@@ -296,9 +333,12 @@ impl JmxvEffHeader {
         let i = match &version {
             b"0010" | b"0011" => i,
             b"0012" | b"0013" => {
-                let (i, v12_unk0) = le_u32(i)?;
+                let (i, v12_unk0) = field("v12_unk0", le_u32)(i)?;
                 header.v12_unk0 = v12_unk0;
-                let (i, unk) = cond(&version == b"0013", tuple((le_u32, le_u32, le_u32)))(i)?;
+                let (i, unk) = cond(
+                    &version == b"0013",
+                    fields!(v13_unk0: le_u32, v13_unk1: le_u32, v13_unk2: le_u32),
+                )(i)?;
                 if let Some((v13_unk0, v13_unk1, v13_unk2)) = unk {
                     header.v13_unk0 = v13_unk0;
                     header.v13_unk1 = v13_unk1;
@@ -314,34 +354,37 @@ impl JmxvEffHeader {
 
 impl EFStoredObject {
     fn parse(i: &[u8]) -> IResult<&[u8], EFStoredObject> {
-        let (_, (data_offset, name, controllers)) =
-            tuple((le_u32, sized_string, parse_objects_u32(EFController::parse)))(i)?;
-        let i = i
-            .get(data_offset as usize + 4..)
-            .ok_or_else(|| nom::Err::Failure(make_error(i, ErrorKind::Eof)))?;
-
-        let (i, res) = tuple((
-            EEGlobalData::parse,
-            EESourceList::parse,
-            EESourceList::parse,
-            EESourceList::parse,
-            EESource::parse,
-            EESourceList::parse,
-            le_u8,
-            le_u8,
-            le_u32,
-            le_u32,
-            le_u32,
-            le_u8,
-            le_u32,
-            le_u8,
-            EESource::parse,
-            EEResource::parse,
-            EESource::parse,
-            EESourceList::parse,
-            EESourceList::parse,
-            parse_objects_u32(EFStoredObject::parse),
-        ))(i)?;
+        let (_, (data_offset, name, controllers)) = fields!(
+            data_offset: le_u32,
+            name: sized_string,
+            controllers: context("controllers", parse_objects_u32(EFController::parse)),
+        )(i)?;
+        // the data offset is relative to the end of the offset itself
+        let (i, res) = at(
+            data_offset.saturating_add(4),
+            fields!(
+                global_data: EEGlobalData::parse,
+                empty_sl0: EESourceList::parse,
+                emitter_sl: EESourceList::parse,
+                empty_sl2: EESourceList::parse,
+                lifetime_source: EESource::parse,
+                program_sl: EESourceList::parse,
+                unkb0: le_u8,
+                unkb1: le_u8,
+                unk0: le_u32,
+                unk1: le_u32,
+                unk2: le_u32,
+                unkb2: le_u8,
+                unk3: le_u32,
+                unkb3: le_u8,
+                view_mode_source: EESource::parse,
+                resource: EEResource::parse,
+                render_source: EESource::parse,
+                empty_sl3: EESourceList::parse,
+                render_sl: EESourceList::parse,
+                children: context("children", parse_objects_u32(EFStoredObject::parse)),
+            ),
+        )(i)?;
         let this = ttr_closure!(name, controllers -> EFStoredObject {
             global_data,
             empty_sl0,
@@ -435,7 +478,14 @@ impl EESourceData {
         cond(
             non_empty,
             flat_map(
-                tuple((sized_string_ref, le_u8, le_u8, le_f32, le_f32, le_f32)),
+                fields!(
+                    command_name: sized_string_ref,
+                    subtype: le_u8,
+                    unkb1: le_u8,
+                    start: le_f32,
+                    end: le_f32,
+                    unkf0: le_f32,
+                ),
                 |(command_name, subtype, unkb1, start, end, unkf0)| {
                     // closure wrap as we need to move command_name here
                     move |i| {
@@ -454,43 +504,46 @@ impl EESourceData {
 
 impl EEResource {
     fn parse(i: &[u8]) -> IResult<&[u8], Self> {
-        map(
-            tuple((
-                map(le_u32, |u32| u32 != 0),
-                le_u32,
-                le_u32,
-                le_u32,
-                le_u32,
-                le_u32,
-                le_u32,
-                le_u32,
-                le_u32,
-                parse_objects_u32(pair(sized_string, parse_objects_u32(sized_string))),
-            )),
-            ttr_closure!(EEResource {
-                two_sided,
-                src_blend,
-                dst_blend,
-                src_texture_arg0,
-                src_texture_arg1,
-                src_texture_op,
-                dst_texture_arg0,
-                dst_texture_arg1,
-                dst_texture_op,
-                meshes,
-            }),
+        context(
+            "EEResource",
+            map(
+                fields!(
+                    two_sided: map(le_u32, |u32| u32 != 0),
+                    src_blend: le_u32,
+                    dst_blend: le_u32,
+                    src_texture_arg0: le_u32,
+                    src_texture_arg1: le_u32,
+                    src_texture_op: le_u32,
+                    dst_texture_arg0: le_u32,
+                    dst_texture_arg1: le_u32,
+                    dst_texture_op: le_u32,
+                    meshes: parse_objects_u32(pair(sized_string, parse_objects_u32(sized_string))),
+                ),
+                ttr_closure!(EEResource {
+                    two_sided,
+                    src_blend,
+                    dst_blend,
+                    src_texture_arg0,
+                    src_texture_arg1,
+                    src_texture_op,
+                    dst_texture_arg0,
+                    dst_texture_arg1,
+                    dst_texture_op,
+                    meshes,
+                }),
+            ),
         )(i)
     }
 }
 
 impl BSAnimation {
-    pub fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+    pub(crate) fn parse(i: &[u8]) -> IResult<&[u8], Self> {
         map(parse_objects_u32(sized_string), Self)(i)
     }
 }
 
 impl ViewMode {
-    pub fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+    pub(crate) fn parse(i: &[u8]) -> IResult<&[u8], Self> {
         let (i, controller) = sized_string_ref(i)?;
         match controller.as_bytes() {
             b"ViewNone" => Ok((i, Self::None)),
@@ -503,7 +556,7 @@ impl ViewMode {
 }
 
 impl RenderShape {
-    pub fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+    pub(crate) fn parse(i: &[u8]) -> IResult<&[u8], Self> {
         let (i, controller) = sized_string_ref(i)?;
         match controller.as_bytes() {
             b"RenderNone" => Ok((i, RenderShape::None)),
@@ -518,7 +571,7 @@ impl RenderShape {
 }
 
 impl EFController {
-    pub fn parse(i: &[u8]) -> IResult<&[u8], EFController> {
+    pub(crate) fn parse(i: &[u8]) -> IResult<&[u8], EFController> {
         use EFController::*;
         let (i, controller) = sized_string_ref(i)?;
         match controller.as_bytes() {
@@ -528,7 +581,12 @@ impl EFController {
             b"Program" => map(EEProgram::parse, Program)(i),
 
             b"LinkMode" => map(
-                tuple((le_u32, le_u32, le_u32, le_u32)),
+                fields!(
+                    unk0: le_u32,
+                    unk1: le_u32,
+                    unk2: le_u32,
+                    unk3: le_u32,
+                ),
                 ttr_closure!(LinkMode {
                     unk0,
                     unk1,
@@ -540,17 +598,20 @@ impl EFController {
 
             b"ViewMode" => map(self::ViewMode::parse, ViewMode)(i),
             b"Shape" => map(
-                pair(RenderShape::parse, EEResource::parse),
+                fields!(
+                    shape: RenderShape::parse,
+                    resource: EEResource::parse,
+                ),
                 ttr_closure!(Shape { shape, resource }),
             )(i),
             b"ScaleGraph" => map(
-                tuple((
-                    <EEBlend<f32>>::parse,
-                    <EEBlend<f32>>::parse,
-                    <EEBlend<f32>>::parse,
-                    le_f32,
-                    le_f32,
-                )),
+                fields!(
+                    scale_x: <EEBlend<f32>>::parse,
+                    scale_y: <EEBlend<f32>>::parse,
+                    scale_z: <EEBlend<f32>>::parse,
+                    float0: le_f32,
+                    float1: le_f32,
+                ),
                 ttr_closure!(ScaleGraph {
                     scale_x,
                     scale_y,
@@ -560,7 +621,10 @@ impl EFController {
                 }),
             )(i),
             b"DiffuseGraph" => map(
-                tuple((<EEBlend<u8>>::parse, <EEBlend<Color>>::parse)),
+                fields!(
+                    scale_x: <EEBlend<u8>>::parse,
+                    scale_y: <EEBlend<Color>>::parse,
+                ),
                 ttr_closure!(DiffuseGraph { scale_x, scale_y }),
             )(i),
             _ => Err(nom::Err::Failure(make_error(i, ErrorKind::Alt))),
@@ -570,9 +634,15 @@ impl EFController {
 
 impl EEGlobalData {
     fn parse(i: &[u8]) -> IResult<&[u8], EEGlobalData> {
-        map(
-            pair(le_u32, parse_objects_u32(EEParameter::parse)),
-            ttr_closure!(EEGlobalData { unk0, parameters }),
+        context(
+            "EEGlobalData",
+            map(
+                fields!(
+                    unk0: le_u32,
+                    parameters: parse_objects_u32(EEParameter::parse),
+                ),
+                ttr_closure!(EEGlobalData { unk0, parameters }),
+            ),
         )(i)
     }
 }
@@ -584,15 +654,24 @@ impl EEProgram {
 }
 impl EFStaticEmit {
     fn parse(i: &[u8]) -> IResult<&[u8], Self> {
-        map(
-            tuple((le_i32, le_i32, le_i32, le_i32, le_f32)),
-            ttr_closure!(EFStaticEmit {
-                min,
-                max,
-                burst_rate,
-                min_particles,
-                spawn_rate
-            }),
+        context(
+            "EFStaticEmit",
+            map(
+                fields!(
+                    min: le_i32,
+                    max: le_i32,
+                    burst_rate: le_i32,
+                    min_particles: le_i32,
+                    spawn_rate: le_f32,
+                ),
+                ttr_closure!(EFStaticEmit {
+                    min,
+                    max,
+                    burst_rate,
+                    min_particles,
+                    spawn_rate
+                }),
+            ),
         )(i)
     }
 }
@@ -615,9 +694,12 @@ impl AxisVector4 {
 
 impl FrameTextureSlide {
     fn parse(i: &[u8]) -> IResult<&[u8], Self> {
-        map(
-            pair(vector3_f32, parse_objects_u32(vector4_f32)),
-            |(first, last)| FrameTextureSlide(first, last),
+        context(
+            "FrameTextureSlide",
+            map(
+                pair(vector3_f32, parse_objects_u32(vector4_f32)),
+                |(first, last)| FrameTextureSlide(first, last),
+            ),
         )(i)
     }
 }
@@ -731,3 +813,44 @@ impl Color {
         map(le_u32, Color)(i)
     }
 }
+
+#[cfg(feature = "serde")]
+#[test]
+fn json_shape() {
+    let controller = EFController::StaticEmit(EFStaticEmit {
+        min: 1,
+        max: 2,
+        burst_rate: 3,
+        min_particles: 4,
+        spawn_rate: 0.5,
+    });
+    let json = serde_json::to_value(&controller).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "StaticEmit": {
+                "min": 1,
+                "max": 2,
+                "burst_rate": 3,
+                "min_particles": 4,
+                "spawn_rate": 0.5,
+            }
+        })
+    );
+    assert_eq!(
+        serde_json::from_value::<EFController>(json).unwrap(),
+        controller
+    );
+    assert_eq!(
+        serde_json::to_value(EFController::NormalTimeLife).unwrap(),
+        "NormalTimeLife"
+    );
+}
+
+#[test]
+fn stored_object_invalid_offset() {
+    let mut data = 0xFFFFu32.to_le_bytes().to_vec();
+    data.extend([0; 8]);
+    let err = finish("EFStoredObject", &data[..], EFStoredObject::parse).unwrap_err();
+    assert_eq!(err.kind(), &crate::ErrorKind::InvalidOffset(0x10003));
+}
@@ -2,18 +2,19 @@ use mint::Vector3;
 use nom::bytes::complete::tag;
 use nom::combinator::{flat_map, map};
 use nom::number::complete::{le_f32, le_u16, le_u32};
-use nom::sequence::{pair, preceded, tuple};
-use nom::IResult;
+use nom::sequence::{pair, preceded};
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
+use crate::error::{finish, IResult, Result};
 use crate::parser_ext::multi::{count, count_indexed, parse_objects_u32};
 use crate::parser_ext::{number::vector3_f32, string::sized_string};
-use crate::ttr_closure;
+use crate::span::{context, field};
+use crate::{fields, ttr_closure};
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GraphPoint {
     Float {
         value: f32,
@@ -29,26 +30,28 @@ impl GraphPoint {
     fn parser<'a>(idx: usize) -> impl Fn(&'a [u8]) -> IResult<&'a [u8], Self> {
         move |i| {
             if idx == 7 || idx == 8 || idx == 10 || idx == 11 || idx == 12 || idx == 15 {
-                map(pair(le_f32, le_f32), |(value, pos_on_graph)| {
-                    GraphPoint::Float {
+                map(
+                    fields!(value: le_f32, pos_on_graph: le_f32),
+                    |(value, pos_on_graph)| GraphPoint::Float {
                         value,
                         pos_on_graph,
-                    }
-                })(i)
+                    },
+                )(i)
             } else {
-                map(pair(vector3_f32, le_f32), |(value, pos_on_graph)| {
-                    GraphPoint::Vector {
+                map(
+                    fields!(value: vector3_f32, pos_on_graph: le_f32),
+                    |(value, pos_on_graph)| GraphPoint::Vector {
                         value,
                         pos_on_graph,
-                    }
-                })(i)
+                    },
+                )(i)
             }
         }
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EnvironmentGroup {
     pub name: Box<str>,
     pub unk0: u16,
@@ -61,36 +64,39 @@ pub struct EnvironmentGroup {
 }
 
 impl EnvironmentGroup {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                sized_string,
-                le_u16,
-                le_u16,
-                le_u16,
-                le_u16,
-                le_u16,
-                le_u16,
-                parse_objects_u32(EnvironmentGroupEntry::parse),
-            )),
-            ttr_closure! {
-                EnvironmentGroup {
-                    name,
-                    unk0,
-                    unk1,
-                    unk2,
-                    unk3,
-                    unk4,
-                    unk5,
-                    entries
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "EnvironmentGroup",
+            map(
+                fields!(
+                    name: sized_string,
+                    unk0: le_u16,
+                    unk1: le_u16,
+                    unk2: le_u16,
+                    unk3: le_u16,
+                    unk4: le_u16,
+                    unk5: le_u16,
+                    entries: parse_objects_u32(EnvironmentGroupEntry::parse),
+                ),
+                ttr_closure! {
+                    EnvironmentGroup {
+                        name,
+                        unk0,
+                        unk1,
+                        unk2,
+                        unk3,
+                        unk4,
+                        unk5,
+                        entries
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EnvironmentGroupEntry {
     pub name: Box<str>,
     pub unk0: u16,
@@ -104,38 +110,41 @@ pub struct EnvironmentGroupEntry {
 }
 
 impl EnvironmentGroupEntry {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                sized_string,
-                le_u16,
-                le_u16,
-                le_u16,
-                le_u16,
-                le_u16,
-                le_u16,
-                le_u16,
-                le_u16,
-            )),
-            ttr_closure! {
-                EnvironmentGroupEntry {
-                    name,
-                    unk0,
-                    unk1,
-                    unk2,
-                    unk3,
-                    unk4,
-                    unk5,
-                    unk6,
-                    unk7
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "EnvironmentGroupEntry",
+            map(
+                fields!(
+                    name: sized_string,
+                    unk0: le_u16,
+                    unk1: le_u16,
+                    unk2: le_u16,
+                    unk3: le_u16,
+                    unk4: le_u16,
+                    unk5: le_u16,
+                    unk6: le_u16,
+                    unk7: le_u16,
+                ),
+                ttr_closure! {
+                    EnvironmentGroupEntry {
+                        name,
+                        unk0,
+                        unk1,
+                        unk2,
+                        unk3,
+                        unk4,
+                        unk5,
+                        unk6,
+                        unk7
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Environment {
     pub id: u16,
     pub name: Box<str>,
@@ -145,30 +154,33 @@ pub struct Environment {
 }
 
 impl Environment {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                le_u16,
-                sized_string,
-                le_u32,
-                le_u32,
-                count_indexed(|i, idx| parse_objects_u32(GraphPoint::parser(idx))(i), 16),
-            )),
-            ttr_closure! {
-                Environment {
-                    id,
-                    name,
-                    unk0,
-                    unk1,
-                    fncs
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "Environment",
+            map(
+                fields!(
+                    id: le_u16,
+                    name: sized_string,
+                    unk0: le_u32,
+                    unk1: le_u32,
+                    fncs: count_indexed(|i, idx| parse_objects_u32(GraphPoint::parser(idx))(i), 16),
+                ),
+                ttr_closure! {
+                    Environment {
+                        id,
+                        name,
+                        unk0,
+                        unk1,
+                        fncs
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxEnvironment {
     pub unk0: u16,
     pub environments: Box<[Environment]>,
@@ -176,15 +188,19 @@ pub struct JmxEnvironment {
 }
 
 impl JmxEnvironment {
-    pub fn parse<'i>(i: &'i [u8]) -> IResult<&'i [u8], Self> {
+    pub fn parse(i: &[u8]) -> Result<Self> {
+        finish("JmxEnvironment", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Self> {
         map(
             preceded(
                 tag(b"JMXVENVI1003"),
-                pair(
-                    flat_map(le_u32, |c| {
-                        pair(le_u16, count(Environment::parse, c as usize))
+                fields!(
+                    environments: flat_map(le_u32, |c| {
+                        pair(field("unk0", le_u16), count(Environment::parse, c as usize))
                     }),
-                    parse_objects_u32(EnvironmentGroup::parse),
+                    environment_groups: parse_objects_u32(EnvironmentGroup::parse),
                 ),
             ),
             |((unk0, environments), environment_groups)| JmxEnvironment {
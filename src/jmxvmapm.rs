@@ -1,41 +1,58 @@
+use std::fmt;
+
+use mint::{Vector2, Vector3};
 use nom::bytes::complete::tag;
 use nom::combinator::map;
-use nom::number::complete::{le_f32, le_u16, le_u32, le_u8};
-use nom::sequence::{preceded, tuple};
-use nom::IResult;
+use nom::number::complete::{le_f32, le_u16, le_u8};
+use nom::sequence::preceded;
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
+use crate::error::{finish, IResult, Result};
+use crate::jmxvnvm::{HEIGHT_MAP_SIZE, HEIGHT_MAP_SPACING};
 use crate::parser_ext::multi::count;
 use crate::parser_ext::string::fixed_string;
-use crate::ttr_closure;
+use crate::span::context;
+use crate::{fields, ttr_closure};
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MapMeshCell {
-    pub height: u32,
+    pub height: f32,
     pub texture: u16,
     pub brightness: u8,
 }
 
 impl MapMeshCell {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((le_u32, le_u16, le_u8)),
-            ttr_closure! {
-                MapMeshCell {
-                    height,
-                    texture,
-                    brightness
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "MapMeshCell",
+            map(
+                fields!(
+                    height: le_f32,
+                    texture: le_u16,
+                    brightness: le_u8,
+                ),
+                ttr_closure! {
+                    MapMeshCell {
+                        height,
+                        texture,
+                        brightness
+                    }
+                },
+            ),
         )(i)
     }
 }
 
+/// The amount of blocks along each side of a region.
+pub const BLOCKS_PER_SIDE: usize = 6;
+/// The amount of cells along each side of a block, neighbouring blocks share their border cells.
+pub const CELLS_PER_BLOCK_SIDE: usize = 17;
+
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MapBlock {
     pub name: Box<str>,
     pub cells: Box<[MapMeshCell]>,
@@ -49,47 +66,350 @@ pub struct MapBlock {
 }
 
 impl MapBlock {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                fixed_string::<6>,
-                count(MapMeshCell::parse, 16 * 16 + 1),
-                le_u8,
-                le_u8,
-                le_f32,
-                count(le_u8, 256),
-                le_f32,
-                le_f32,
-                count(le_u8, 20),
-            )),
-            ttr_closure! {
-                MapBlock {
-                    name,
-                    cells,
-                    density,
-                    unk0,
-                    sea_level,
-                    extra_data,
-                    height_min,
-                    height_max,
-                    unk0_buffer
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "MapBlock",
+            map(
+                fields!(
+                    name: fixed_string::<6>,
+                    cells: count(
+                        MapMeshCell::parse,
+                        CELLS_PER_BLOCK_SIDE * CELLS_PER_BLOCK_SIDE,
+                    ),
+                    density: le_u8,
+                    unk0: le_u8,
+                    sea_level: le_f32,
+                    extra_data: count(le_u8, 256),
+                    height_min: le_f32,
+                    height_max: le_f32,
+                    unk0_buffer: count(le_u8, 20),
+                ),
+                ttr_closure! {
+                    MapBlock {
+                        name,
+                        cells,
+                        density,
+                        unk0,
+                        sea_level,
+                        extra_data,
+                        height_min,
+                        height_max,
+                        unk0_buffer
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxMapMesh {
     pub blocks: Box<[MapBlock]>,
 }
 
+/// A vertex of a [`TerrainMesh`].
+#[derive(Copy, Clone, Debug)]
+pub struct TerrainVertex {
+    pub position: Vector3<f32>,
+    pub normal: Vector3<f32>,
+    /// The position in cells, so that textures repeat once per cell.
+    pub uv: Vector2<f32>,
+    /// The raw texture field of the cell.
+    pub texture: u16,
+    pub brightness: u8,
+}
+
+/// An indexed triangle list of the terrain of a region, with positions local to the region.
+#[derive(Debug)]
+pub struct TerrainMesh {
+    pub vertices: Box<[TerrainVertex]>,
+    pub indices: Box<[u32]>,
+}
+
+/// An indexed triangle list of the water surfaces of a region, with positions local to the region.
+#[derive(Debug)]
+pub struct WaterMesh {
+    pub positions: Box<[Vector3<f32>]>,
+    pub indices: Box<[u32]>,
+}
+
+/// The error returned when the blocks of a [`JmxMapMesh`] don't form a whole region, which
+/// parsed meshes always do.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LayoutError {
+    /// The mesh has the given amount of blocks instead of [`BLOCKS_PER_SIDE`]².
+    BlockCount(usize),
+    /// The block at index `block` has `count` cells instead of [`CELLS_PER_BLOCK_SIDE`]².
+    CellCount { block: usize, count: usize },
+}
+
+impl std::error::Error for LayoutError {}
+impl fmt::Display for LayoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutError::BlockCount(count) => write!(
+                f,
+                "expected {} blocks, found {}",
+                BLOCKS_PER_SIDE * BLOCKS_PER_SIDE,
+                count
+            ),
+            LayoutError::CellCount { block, count } => write!(
+                f,
+                "expected {} cells in block {}, found {}",
+                CELLS_PER_BLOCK_SIDE * CELLS_PER_BLOCK_SIDE,
+                block,
+                count
+            ),
+        }
+    }
+}
+
+/// The indices of two counter-clockwise triangles, seen from above, per cell of a grid with
+/// `size` vertices along each side.
+fn grid_indices(size: usize) -> impl Iterator<Item = u32> {
+    (0..size - 1)
+        .flat_map(move |z| (0..size - 1).map(move |x| (x, z)))
+        .flat_map(move |(x, z)| {
+            let idx = |x: usize, z: usize| (z * size + x) as u32;
+            [
+                idx(x, z),
+                idx(x, z + 1),
+                idx(x + 1, z),
+                idx(x + 1, z),
+                idx(x, z + 1),
+                idx(x + 1, z + 1),
+            ]
+        })
+}
+
 impl JmxMapMesh {
-    pub fn parse<'i>(i: &'i [u8]) -> IResult<&'i [u8], Self> {
+    fn check_layout(&self) -> Result<(), LayoutError> {
+        if self.blocks.len() != BLOCKS_PER_SIDE * BLOCKS_PER_SIDE {
+            return Err(LayoutError::BlockCount(self.blocks.len()));
+        }
+        match self
+            .blocks
+            .iter()
+            .position(|block| block.cells.len() != CELLS_PER_BLOCK_SIDE * CELLS_PER_BLOCK_SIDE)
+        {
+            Some(block) => Err(LayoutError::CellCount {
+                block,
+                count: self.blocks[block].cells.len(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// The cell at the given position of the grid spanning all blocks, with the border cells
+    /// shared by neighbouring blocks taken from the first block.
+    fn cell(&self, x: usize, z: usize) -> &MapMeshCell {
+        let steps = CELLS_PER_BLOCK_SIDE - 1;
+        let (block_x, block_z) = (x.saturating_sub(1) / steps, z.saturating_sub(1) / steps);
+        let (cell_x, cell_z) = (x - block_x * steps, z - block_z * steps);
+        &self.blocks[block_z * BLOCKS_PER_SIDE + block_x].cells
+            [cell_z * CELLS_PER_BLOCK_SIDE + cell_x]
+    }
+
+    /// Builds the terrain of the region as a single grid of [`HEIGHT_MAP_SIZE`] vertices along
+    /// each side, stitching the blocks together at their shared borders.
+    ///
+    /// The blocks and their cells are laid out row by row along z, the normals are derived from
+    /// the neighbouring heights.
+    pub fn terrain_mesh(&self) -> Result<TerrainMesh, LayoutError> {
+        self.check_layout()?;
+        let size = HEIGHT_MAP_SIZE;
+        let height = |x: usize, z: usize| self.cell(x, z).height;
+        let vertices = (0..size)
+            .flat_map(|z| (0..size).map(move |x| (x, z)))
+            .map(|(x, z)| {
+                let cell = self.cell(x, z);
+                let (left, right) = (x.saturating_sub(1), (x + 1).min(size - 1));
+                let (back, front) = (z.saturating_sub(1), (z + 1).min(size - 1));
+                let dx = (height(right, z) - height(left, z))
+                    / ((right - left) as f32 * HEIGHT_MAP_SPACING);
+                let dz = (height(x, front) - height(x, back))
+                    / ((front - back) as f32 * HEIGHT_MAP_SPACING);
+                let len = (dx * dx + 1.0 + dz * dz).sqrt();
+                TerrainVertex {
+                    position: Vector3 {
+                        x: x as f32 * HEIGHT_MAP_SPACING,
+                        y: cell.height,
+                        z: z as f32 * HEIGHT_MAP_SPACING,
+                    },
+                    normal: Vector3 {
+                        x: -dx / len,
+                        y: 1.0 / len,
+                        z: -dz / len,
+                    },
+                    uv: Vector2 {
+                        x: x as f32,
+                        y: z as f32,
+                    },
+                    texture: cell.texture,
+                    brightness: cell.brightness,
+                }
+            })
+            .collect();
+        Ok(TerrainMesh {
+            vertices,
+            indices: grid_indices(size).collect(),
+        })
+    }
+
+    /// Builds a quad at the `sea_level` of every block whose sea level lies above its lowest
+    /// terrain height, as the water of the other blocks would be hidden by the terrain.
+    ///
+    /// Returns `None` if no block has visible water.
+    pub fn water_mesh(&self) -> Result<Option<WaterMesh>, LayoutError> {
+        self.check_layout()?;
+        let block_size = (CELLS_PER_BLOCK_SIDE - 1) as f32 * HEIGHT_MAP_SPACING;
+        let mut positions = Vec::new();
+        let mut indices = Vec::new();
+        for (idx, block) in self.blocks.iter().enumerate() {
+            let lowest = block
+                .cells
+                .iter()
+                .map(|cell| cell.height)
+                .fold(f32::INFINITY, f32::min);
+            if block.sea_level <= lowest {
+                continue;
+            }
+            let (block_x, block_z) = (
+                (idx % BLOCKS_PER_SIDE) as f32,
+                (idx / BLOCKS_PER_SIDE) as f32,
+            );
+            let base = positions.len() as u32;
+            positions.extend(
+                [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)].map(|(x, z)| Vector3 {
+                    x: (block_x + x) * block_size,
+                    y: block.sea_level,
+                    z: (block_z + z) * block_size,
+                }),
+            );
+            indices.extend(grid_indices(2).map(|idx| base + idx));
+        }
+        Ok((!positions.is_empty()).then(|| WaterMesh {
+            positions: positions.into(),
+            indices: indices.into(),
+        }))
+    }
+
+    pub fn parse(i: &[u8]) -> Result<Self> {
+        finish("JmxMapMesh", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Self> {
         map(
-            preceded(tag(b"JMXVMAPM1000"), count(MapBlock::parse, 6 * 6)),
+            preceded(
+                tag(b"JMXVMAPM1000"),
+                count(MapBlock::parse, BLOCKS_PER_SIDE * BLOCKS_PER_SIDE),
+            ),
             |blocks| JmxMapMesh { blocks },
         )(i)
     }
 }
+
+#[test]
+fn terrain_mesh_stitches_blocks() {
+    let blocks = (0..BLOCKS_PER_SIDE * BLOCKS_PER_SIDE)
+        .map(|idx| {
+            let (block_x, block_z) = (idx % BLOCKS_PER_SIDE, idx / BLOCKS_PER_SIDE);
+            let cells = (0..CELLS_PER_BLOCK_SIDE * CELLS_PER_BLOCK_SIDE)
+                .map(|cell| {
+                    let x = block_x * 16 + cell % CELLS_PER_BLOCK_SIDE;
+                    let z = block_z * 16 + cell / CELLS_PER_BLOCK_SIDE;
+                    MapMeshCell {
+                        height: (x + 2 * z) as f32,
+                        texture: idx as u16,
+                        brightness: 0xFF,
+                    }
+                })
+                .collect();
+            MapBlock {
+                name: "block".into(),
+                cells,
+                density: 0,
+                unk0: 0,
+                sea_level: if idx == 7 { 1000.0 } else { -1000.0 },
+                extra_data: Box::new([]),
+                height_min: 0.0,
+                height_max: 0.0,
+                unk0_buffer: Box::new([]),
+            }
+        })
+        .collect();
+    let mut mesh = JmxMapMesh { blocks };
+    let terrain = mesh.terrain_mesh().unwrap();
+    assert_eq!(terrain.vertices.len(), HEIGHT_MAP_SIZE * HEIGHT_MAP_SIZE);
+    assert_eq!(terrain.indices.len(), 96 * 96 * 6);
+    let vertex = terrain.vertices[20 * HEIGHT_MAP_SIZE + 16];
+    assert_eq!(vertex.position.y, 56.0);
+    assert_eq!(vertex.position.x, 16.0 * HEIGHT_MAP_SPACING);
+    // the border column is taken from the first of both blocks
+    assert_eq!(vertex.texture, 6);
+    assert!(vertex.normal.x < 0.0 && vertex.normal.z < 0.0 && vertex.normal.y > 0.0);
+
+    let water = mesh.water_mesh().unwrap().unwrap();
+    assert_eq!(water.positions.len(), 4);
+    assert_eq!(water.positions[0].x, 16.0 * HEIGHT_MAP_SPACING);
+    assert_eq!(water.positions[0].z, 16.0 * HEIGHT_MAP_SPACING);
+
+    let mut blocks = std::mem::take(&mut mesh.blocks).into_vec();
+    blocks[3].cells = Box::new([]);
+    mesh.blocks = blocks.into();
+    assert_eq!(
+        mesh.terrain_mesh().unwrap_err(),
+        LayoutError::CellCount { block: 3, count: 0 }
+    );
+    mesh.blocks = Box::new([]);
+    assert_eq!(mesh.water_mesh().unwrap_err(), LayoutError::BlockCount(0));
+}
+
+#[test]
+fn parse_map_block() {
+    let mut block = b"blk001".to_vec();
+    for cell in 0..CELLS_PER_BLOCK_SIDE * CELLS_PER_BLOCK_SIDE {
+        block.extend((cell as f32).to_le_bytes());
+        block.extend((cell as u16 | 0x0400).to_le_bytes());
+        block.push(cell as u8);
+    }
+    block.extend([2, 3]);
+    block.extend(12.5f32.to_le_bytes());
+    block.extend((0..=255).collect::<Vec<u8>>());
+    block.extend((-1.0f32).to_le_bytes());
+    block.extend(288.0f32.to_le_bytes());
+    block.extend([0xAB; 20]);
+    assert_eq!(block.len(), 6 + 17 * 17 * 7 + 1 + 1 + 4 + 256 + 4 + 4 + 20);
+
+    let (rest, parsed) = MapBlock::parse(&block).unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(&*parsed.name, "blk001");
+    let cell = &parsed.cells[18];
+    assert_eq!(
+        (cell.height, cell.texture, cell.brightness),
+        (18.0, 0x0412, 18)
+    );
+    assert_eq!(
+        (parsed.density, parsed.unk0, parsed.sea_level),
+        (2, 3, 12.5)
+    );
+    assert_eq!(parsed.extra_data[255], 255);
+    assert_eq!((parsed.height_min, parsed.height_max), (-1.0, 288.0));
+    assert_eq!(&*parsed.unk0_buffer, &[0xAB; 20]);
+
+    let (_, spans) = crate::span::record(&block, MapBlock::parse);
+    let classes = crate::span::classify(block.len(), &spans);
+    assert_eq!(classes[2029], crate::span::ByteClass::Known);
+    assert_eq!(classes[2030], crate::span::ByteClass::Unknown);
+
+    let mut file = b"JMXVMAPM1000".to_vec();
+    for _ in 0..BLOCKS_PER_SIDE * BLOCKS_PER_SIDE {
+        file.extend(&block);
+    }
+    let mesh = JmxMapMesh::parse(&file).unwrap();
+    assert_eq!(mesh.blocks.len(), 36);
+    assert_eq!(mesh.blocks[35].cells[288].height, 288.0);
+    // a region missing a block is rejected
+    assert!(JmxMapMesh::parse(&file[..file.len() - block.len()]).is_err());
+}
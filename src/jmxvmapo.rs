@@ -2,18 +2,20 @@ use mint::Vector3;
 use nom::bytes::complete::tag;
 use nom::combinator::map;
 use nom::number::complete::{le_f32, le_u16, le_u32};
-use nom::sequence::{preceded, tuple};
-use nom::IResult;
+use nom::sequence::preceded;
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
+use crate::error::{finish, IResult, Result};
 use crate::parser_ext::multi::{count, parse_objects_u16};
 use crate::parser_ext::number::vector3_f32;
-use crate::ttr_closure;
+use crate::region::RegionId;
+use crate::span::context;
+use crate::{fields, ttr_closure};
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MapObject {
     pub id: u32,
     pub position: Vector3<f32>,
@@ -21,36 +23,47 @@ pub struct MapObject {
     pub theta: f32,
     pub unique_id: u32,
     pub scale: u16,
-    pub region: u16,
+    pub region: RegionId,
 }
 
 impl MapObject {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((le_u32, vector3_f32, le_u16, le_f32, le_u32, le_u16, le_u16)),
-            ttr_closure! {
-                MapObject {
-                    id,
-                    position,
-                    visibility_flag,
-                    theta,
-                    unique_id,
-                    scale,
-                    region
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "MapObject",
+            map(
+                fields!(
+                    id: le_u32,
+                    position: vector3_f32,
+                    visibility_flag: le_u16,
+                    theta: le_f32,
+                    unique_id: le_u32,
+                    scale: le_u16,
+                    region: RegionId::parse,
+                ),
+                ttr_closure! {
+                    MapObject {
+                        id,
+                        position,
+                        visibility_flag,
+                        theta,
+                        unique_id,
+                        scale,
+                        region
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct MapObjectGroup {
     pub entries: Box<[MapObject]>,
 }
 
 impl MapObjectGroup {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
         map(parse_objects_u16(MapObject::parse), |entries| {
             MapObjectGroup { entries }
         })(i)
@@ -58,13 +71,17 @@ impl MapObjectGroup {
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxMapObject {
     pub objects: Box<[MapObjectGroup]>,
 }
 
 impl JmxMapObject {
-    pub fn parse<'i>(i: &'i [u8]) -> IResult<&'i [u8], Self> {
+    pub fn parse(i: &[u8]) -> Result<Self> {
+        finish("JmxMapObject", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Self> {
         map(
             preceded(tag(b"JMXVMAPO1001"), count(MapObjectGroup::parse, 144)),
             |objects| JmxMapObject { objects },
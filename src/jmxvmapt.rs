@@ -4,12 +4,15 @@ use nom::number::complete::{le_u32, le_u8};
 use nom::sequence::{pair, preceded};
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
+use crate::error::{finish, IResult, Result};
+use crate::fields;
 use crate::parser_ext::multi::count;
+use crate::span::field;
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxMapTexture {
     pub shadow_map_tiles: Box<[u8]>, //9216
     pub header_len: u32,
@@ -17,13 +20,19 @@ pub struct JmxMapTexture {
 }
 
 impl JmxMapTexture {
-    pub fn parse<'i>(i: &'i [u8]) -> nom::IResult<&'i [u8], Self> {
+    pub fn parse(i: &[u8]) -> Result<Self> {
+        finish("JmxMapTexture", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Self> {
         map(
             preceded(
                 tag("JMXVMAPT 1001"),
-                pair(
-                    count(le_u8, 9216),
-                    flat_map(le_u32, |c| pair(le_u32, count(le_u8, c as usize))),
+                fields!(
+                    shadow_map_tiles: count(le_u8, 9216),
+                    data: flat_map(le_u32, |c| {
+                        pair(field("header_len", le_u32), count(le_u8, c as usize))
+                    }),
                 ),
             ),
             |(shadow_map_tiles, (header_len, data))| JmxMapTexture {
@@ -4,19 +4,24 @@ use nom::number::complete::le_u8;
 use nom::sequence::preceded;
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
+use crate::error::{finish, IResult, Result};
 use crate::parser_ext::multi::count;
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxMapInfo {
     //BitArray: 256 * 256 = 65536 bits / 8 = 8192 bytes
     pub region_data: Box<[u8]>,
 }
 
 impl JmxMapInfo {
-    pub fn parse<'i>(i: &'i [u8]) -> nom::IResult<&'i [u8], Self> {
+    pub fn parse(i: &[u8]) -> Result<Self> {
+        finish("JmxMapInfo", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Self> {
         map(
             preceded(
                 tag("JMXVMFO 1000"),
@@ -4,18 +4,20 @@ use nom::bytes::complete::tag;
 use nom::combinator::{flat_map, map};
 use nom::number::complete::{le_f32, le_u16, le_u32, le_u8};
 use nom::sequence::{pair, preceded, tuple};
-use nom::IResult;
 
+use crate::error::{finish, IResult, Result};
 use crate::parser_ext::flags::flags_u16;
 use crate::parser_ext::multi::{count, parse_objects_u16, parse_objects_u32, parse_objects_u8};
 use crate::parser_ext::number::{vector2_f32, vector3_f32};
-use crate::ttr_closure;
+use crate::region::RegionId;
+use crate::span::{context, field};
+use crate::{fields, ttr_closure};
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
 bitflags! {
-    #[cfg_attr(feature = "serde", derive(Serialize))]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     #[cfg_attr(feature = "serde", serde(transparent))]
     pub struct CollisionFlag: u16 {
         const HAS_COLLISION = 0xFFFF;
@@ -23,7 +25,7 @@ bitflags! {
 }
 
 bitflags! {
-    #[cfg_attr(feature = "serde", derive(Serialize))]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     #[cfg_attr(feature = "serde", serde(transparent))]
     pub struct EventZoneFlag: u16 {
         const UNK0 = 0x1;
@@ -32,7 +34,7 @@ bitflags! {
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NavEntry {
     pub id: u32,
     pub position: Vector3<f32>,
@@ -41,43 +43,48 @@ pub struct NavEntry {
     pub unique_id: u16,
     pub scale: u16,
     pub event_zone_flag: EventZoneFlag,
-    pub region_id: u16,
+    pub region_id: RegionId,
     pub mount_points: Box<[(u8, u8, u8, u8, u8, u8)]>,
 }
 
 impl NavEntry {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                le_u32,
-                vector3_f32,
-                flags_u16(CollisionFlag::from_bits),
-                le_f32,
-                le_u16,
-                le_u16,
-                flags_u16(EventZoneFlag::from_bits),
-                le_u16,
-                parse_objects_u16(tuple((le_u8, le_u8, le_u8, le_u8, le_u8, le_u8))),
-            )),
-            ttr_closure! {
-                NavEntry {
-                    id,
-                    position,
-                    collision_flag,
-                    yaw,
-                    unique_id,
-                    scale,
-                    event_zone_flag,
-                    region_id,
-                    mount_points
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "NavEntry",
+            map(
+                fields!(
+                    id: le_u32,
+                    position: vector3_f32,
+                    collision_flag: flags_u16(CollisionFlag::from_bits_unchecked),
+                    yaw: le_f32,
+                    unique_id: le_u16,
+                    scale: le_u16,
+                    event_zone_flag: flags_u16(EventZoneFlag::from_bits_unchecked),
+                    region_id: RegionId::parse,
+                    mount_points: parse_objects_u16(tuple((
+                        le_u8, le_u8, le_u8, le_u8, le_u8, le_u8,
+                    ))),
+                ),
+                ttr_closure! {
+                    NavEntry {
+                        id,
+                        position,
+                        collision_flag,
+                        yaw,
+                        unique_id,
+                        scale,
+                        event_zone_flag,
+                        region_id,
+                        mount_points
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NavCell {
     pub min: Vector2<f32>,
     pub max: Vector2<f32>,
@@ -85,22 +92,29 @@ pub struct NavCell {
 }
 
 impl NavCell {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((vector2_f32, vector2_f32, parse_objects_u8(le_u16))),
-            ttr_closure! {
-                NavCell {
-                    min,
-                    max,
-                    entries
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "NavCell",
+            map(
+                fields!(
+                    min: vector2_f32,
+                    max: vector2_f32,
+                    entries: parse_objects_u8(le_u16),
+                ),
+                ttr_closure! {
+                    NavCell {
+                        min,
+                        max,
+                        entries
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NavRegionLink {
     pub min: Vector2<f32>,
     pub max: Vector2<f32>,
@@ -109,43 +123,46 @@ pub struct NavRegionLink {
     pub line_destination: u8,
     pub cell_source: u16,
     pub cell_destination: u16,
-    pub region_source: u16,
-    pub region_destination: u16,
+    pub region_source: RegionId,
+    pub region_destination: RegionId,
 }
 
 impl NavRegionLink {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                vector2_f32,
-                vector2_f32,
-                le_u8,
-                le_u8,
-                le_u8,
-                le_u16,
-                le_u16,
-                le_u16,
-                le_u16,
-            )),
-            ttr_closure! {
-                    NavRegionLink {
-                    min,
-                    max,
-                    line_flag,
-                    line_source,
-                    line_destination,
-                    cell_source,
-                    cell_destination,
-                    region_source,
-                    region_destination
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "NavRegionLink",
+            map(
+                fields!(
+                    min: vector2_f32,
+                    max: vector2_f32,
+                    line_flag: le_u8,
+                    line_source: le_u8,
+                    line_destination: le_u8,
+                    cell_source: le_u16,
+                    cell_destination: le_u16,
+                    region_source: RegionId::parse,
+                    region_destination: RegionId::parse,
+                ),
+                ttr_closure! {
+                        NavRegionLink {
+                        min,
+                        max,
+                        line_flag,
+                        line_source,
+                        line_destination,
+                        cell_source,
+                        cell_destination,
+                        region_source,
+                        region_destination
+                    }
+                },
+            ),
         )(i)
     }
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NavCellLink {
     pub min: Vector2<f32>,
     pub max: Vector2<f32>,
@@ -157,34 +174,56 @@ pub struct NavCellLink {
 }
 
 impl NavCellLink {
-    fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        map(
-            tuple((
-                vector2_f32,
-                vector2_f32,
-                le_u8,
-                le_u8,
-                le_u8,
-                le_u16,
-                le_u16,
-            )),
-            ttr_closure! {
-                NavCellLink {
-                    min,
-                    max,
-                    line_flag,
-                    line_source,
-                    line_destination,
-                    cell_source,
-                    cell_destination
-                }
-            },
+    fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        context(
+            "NavCellLink",
+            map(
+                fields!(
+                    min: vector2_f32,
+                    max: vector2_f32,
+                    line_flag: le_u8,
+                    line_source: le_u8,
+                    line_destination: le_u8,
+                    cell_source: le_u16,
+                    cell_destination: le_u16,
+                ),
+                ttr_closure! {
+                    NavCellLink {
+                        min,
+                        max,
+                        line_flag,
+                        line_source,
+                        line_destination,
+                        cell_source,
+                        cell_destination
+                    }
+                },
+            ),
         )(i)
     }
 }
 
+/// The length of a region's sides in world units.
+pub const REGION_SIZE: f32 = 1920.0;
+/// The amount of height samples along each side of a region.
+pub const HEIGHT_MAP_SIZE: usize = 97;
+/// The distance between two neighbouring height samples in world units.
+pub const HEIGHT_MAP_SPACING: f32 = REGION_SIZE / (HEIGHT_MAP_SIZE - 1) as f32;
+
+impl NavCell {
+    /// Whether the point lies within the cell, including its minimum but excluding its maximum
+    /// edges, so that points on an edge shared by two cells belong to only one of them.
+    ///
+    /// Maximum edges on the far border of the region are included, as no other cell covers them.
+    pub fn contains(&self, x: f32, z: f32) -> bool {
+        let within =
+            |v: f32, min: f32, max: f32| min <= v && (v < max || (max >= REGION_SIZE && v <= max));
+        within(x, self.min.x, self.max.x) && within(z, self.min.y, self.max.y)
+    }
+}
+
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct JmxNvm {
     pub nav_entries: Box<[NavEntry]>,
     pub nav_extra_count: u32,
@@ -196,18 +235,73 @@ pub struct JmxNvm {
 }
 
 impl JmxNvm {
-    pub fn parse<'i>(i: &'i [u8]) -> IResult<&'i [u8], Self> {
+    /// The terrain height at the region local position, bilinearly interpolated between the
+    /// surrounding height map samples.
+    ///
+    /// The height map is stored row by row along z. Positions outside of the region are clamped
+    /// to its border. Returns `None` if the height map has less than
+    /// [`HEIGHT_MAP_SIZE`]² samples.
+    pub fn height_at(&self, x: f32, z: f32) -> Option<f32> {
+        if self.height_map.len() < HEIGHT_MAP_SIZE * HEIGHT_MAP_SIZE {
+            return None;
+        }
+        let last = (HEIGHT_MAP_SIZE - 1) as f32;
+        let gx = (x / HEIGHT_MAP_SPACING).clamp(0.0, last);
+        let gz = (z / HEIGHT_MAP_SPACING).clamp(0.0, last);
+        let (x0, z0) = (
+            (gx as usize).min(HEIGHT_MAP_SIZE - 2),
+            (gz as usize).min(HEIGHT_MAP_SIZE - 2),
+        );
+        let (tx, tz) = (gx - x0 as f32, gz - z0 as f32);
+        let height = |x: usize, z: usize| self.height_map[z * HEIGHT_MAP_SIZE + x];
+        let near = height(x0, z0) + (height(x0 + 1, z0) - height(x0, z0)) * tx;
+        let far = height(x0, z0 + 1) + (height(x0 + 1, z0 + 1) - height(x0, z0 + 1)) * tx;
+        Some(near + (far - near) * tz)
+    }
+
+    /// The index of the first nav cell containing the region local position.
+    pub fn nav_cell_index_at(&self, x: f32, z: f32) -> Option<usize> {
+        self.nav_cells.iter().position(|cell| cell.contains(x, z))
+    }
+
+    /// The first nav cell containing the region local position.
+    pub fn nav_cell_at(&self, x: f32, z: f32) -> Option<&NavCell> {
+        self.nav_cells.iter().find(|cell| cell.contains(x, z))
+    }
+
+    pub fn parse(i: &[u8]) -> Result<Self> {
+        finish("JmxNvm", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Self> {
         map(
             preceded(
                 tag(b"JMXVNVM 1000"),
-                tuple((
-                    parse_objects_u16(NavEntry::parse),
-                    flat_map(le_u32, |c| pair(le_u32, count(NavCell::parse, c as usize))),
-                    parse_objects_u32(NavRegionLink::parse),
-                    parse_objects_u32(NavCellLink::parse),
-                    count(tuple((le_u16, le_u16, le_u16, le_u16)), 96 * 96),
-                    count(le_f32, 97 * 97),
-                )),
+                fields!(
+                    nav_entries: context("nav_entries", parse_objects_u16(NavEntry::parse)),
+                    nav_cells: flat_map(le_u32, |c| {
+                        pair(
+                            field("nav_extra_count", le_u32),
+                            context("nav_cells", count(NavCell::parse, c as usize)),
+                        )
+                    }),
+                    nav_region_links: context(
+                        "nav_region_links",
+                        parse_objects_u32(NavRegionLink::parse),
+                    ),
+                    nav_cell_links: context(
+                        "nav_cell_links",
+                        parse_objects_u32(NavCellLink::parse),
+                    ),
+                    texture_map: context(
+                        "texture_map",
+                        count(tuple((le_u16, le_u16, le_u16, le_u16)), 96 * 96),
+                    ),
+                    height_map: context(
+                        "height_map",
+                        count(le_f32, HEIGHT_MAP_SIZE * HEIGHT_MAP_SIZE),
+                    ),
+                ),
             ),
             |data| JmxNvm {
                 nav_entries: data.0,
@@ -221,3 +315,52 @@ impl JmxNvm {
         )(i)
     }
 }
+
+#[test]
+fn height_and_nav_cell_queries() {
+    let plane = |x: f32, z: f32| 0.5 * x - 0.25 * z + 10.0;
+    let height_map = (0..HEIGHT_MAP_SIZE * HEIGHT_MAP_SIZE)
+        .map(|idx| {
+            let (x, z) = (idx % HEIGHT_MAP_SIZE, idx / HEIGHT_MAP_SIZE);
+            plane(x as f32 * HEIGHT_MAP_SPACING, z as f32 * HEIGHT_MAP_SPACING)
+        })
+        .collect();
+    let cell = |min_x, max_x| NavCell {
+        min: Vector2 { x: min_x, y: 0.0 },
+        max: Vector2 {
+            x: max_x,
+            y: REGION_SIZE,
+        },
+        entries: Box::new([]),
+    };
+    let nvm = JmxNvm {
+        nav_entries: Box::new([]),
+        nav_extra_count: 0,
+        nav_cells: Box::new([cell(0.0, 960.0), cell(960.0, REGION_SIZE)]),
+        nav_region_links: Box::new([]),
+        nav_cell_links: Box::new([]),
+        texture_map: Box::new([]),
+        height_map,
+    };
+    for (x, z) in [
+        (0.0, 0.0),
+        (13.0, 7.5),
+        (1000.3, 1919.0),
+        (REGION_SIZE, REGION_SIZE),
+    ] {
+        assert!((nvm.height_at(x, z).unwrap() - plane(x, z)).abs() < 1e-3);
+    }
+    assert_eq!(nvm.height_at(-50.0, 0.0), Some(plane(0.0, 0.0)));
+    assert_eq!(nvm.nav_cell_index_at(960.0, 5.0), Some(1));
+    assert_eq!(nvm.nav_cell_index_at(2000.0, 5.0), None);
+    // the far border of the region belongs to the cells along it
+    assert_eq!(nvm.nav_cell_index_at(REGION_SIZE, 5.0), Some(1));
+    assert_eq!(nvm.nav_cell_index_at(REGION_SIZE, REGION_SIZE), Some(1));
+    assert_eq!(nvm.nav_cell_index_at(500.0, REGION_SIZE), Some(0));
+
+    let short = JmxNvm {
+        height_map: Box::new([0.0; 96]),
+        ..nvm
+    };
+    assert_eq!(short.height_at(0.0, 0.0), None);
+}
@@ -1,27 +1,28 @@
 use nom::bytes::complete::tag;
 use nom::character::complete::{line_ending, multispace1};
 use nom::combinator::{flat_map, map};
-use nom::multi::many_m_n;
-use nom::sequence::{preceded, terminated, tuple};
-use nom::IResult;
+use nom::sequence::{preceded, terminated};
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
 use std::path::Path;
 
+use crate::error::{finish, IResult, Result};
+use crate::parser_ext::multi::count;
 use crate::parser_ext::text::{
     parse_quoted_path_buf, parse_quoted_string, parse_u16_str, parse_u32_hex_str, parse_u8_str,
 };
-use crate::ttr_closure;
+use crate::span::context;
+use crate::{fields, ttr_closure};
 
-fn parse_f32_hex_dumped_str<'i>(input: &'i str) -> IResult<&'i str, f32> {
+fn parse_f32_hex_dumped_str(input: &str) -> IResult<&str, f32> {
     map(parse_u32_hex_str, |num| {
         f32::from_le_bytes(num.to_le_bytes())
     })(input)
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, PartialEq)]
 pub struct ObjectStringIfo {
     pub index: u32,
@@ -36,51 +37,58 @@ pub struct ObjectStringIfo {
 }
 
 impl ObjectStringIfo {
-    pub fn parse<'i>(i: &'i str) -> IResult<&'i str, Vec<ObjectStringIfo>> {
+    pub fn parse(i: &str) -> Result<Vec<ObjectStringIfo>> {
+        finish("ObjectStringIfo", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &str) -> IResult<&str, Vec<ObjectStringIfo>> {
         preceded(
             tag("JMXVOBJI1000\n"),
-            flat_map(terminated(parse_u16_str, line_ending), |count| {
-                many_m_n(count as usize, count as usize, Self::parse_single)
+            flat_map(terminated(parse_u16_str, line_ending), |c| {
+                map(count(Self::parse_single, c as usize), Vec::from)
             }),
         )(i)
     }
 }
 
 impl ObjectStringIfo {
-    fn parse_single<'i>(i: &'i str) -> IResult<&'i str, Self> {
-        terminated(
-            map(
-                tuple((
-                    parse_u32_hex_str,
-                    preceded(multispace1, parse_u32_hex_str),
-                    preceded(multispace1, parse_u8_str),
-                    preceded(multispace1, parse_u8_str),
-                    preceded(multispace1, parse_f32_hex_dumped_str),
-                    preceded(multispace1, parse_f32_hex_dumped_str),
-                    preceded(multispace1, parse_f32_hex_dumped_str),
-                    preceded(multispace1, parse_f32_hex_dumped_str),
-                    preceded(multispace1, parse_quoted_string),
-                )),
-                ttr_closure! {
-                    ObjectStringIfo {
-                        index,
-                        flag,
-                        x_sec,
-                        y_sec,
-                        x_offset,
-                        y_offset,
-                        z_offset,
-                        yaw,
-                        string,
-                    }
-                },
+    fn parse_single(i: &str) -> IResult<&str, Self> {
+        context(
+            "ObjectStringIfo",
+            terminated(
+                map(
+                    fields!(
+                        index: parse_u32_hex_str,
+                        flag: preceded(multispace1, parse_u32_hex_str),
+                        x_sec: preceded(multispace1, parse_u8_str),
+                        y_sec: preceded(multispace1, parse_u8_str),
+                        x_offset: preceded(multispace1, parse_f32_hex_dumped_str),
+                        y_offset: preceded(multispace1, parse_f32_hex_dumped_str),
+                        z_offset: preceded(multispace1, parse_f32_hex_dumped_str),
+                        yaw: preceded(multispace1, parse_f32_hex_dumped_str),
+                        string: preceded(multispace1, parse_quoted_string),
+                    ),
+                    ttr_closure! {
+                        ObjectStringIfo {
+                            index,
+                            flag,
+                            x_sec,
+                            y_sec,
+                            x_offset,
+                            y_offset,
+                            z_offset,
+                            yaw,
+                            string,
+                        }
+                    },
+                ),
+                line_ending,
             ),
-            line_ending,
         )(i)
     }
 }
 
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Debug, PartialEq)]
 pub struct ObjectIfo {
     pub index: u16,
@@ -89,32 +97,39 @@ pub struct ObjectIfo {
 }
 
 impl ObjectIfo {
-    pub fn parse<'i>(i: &'i str) -> IResult<&'i str, Vec<ObjectIfo>> {
+    pub fn parse(i: &str) -> Result<Vec<ObjectIfo>> {
+        finish("ObjectIfo", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &str) -> IResult<&str, Vec<ObjectIfo>> {
         preceded(
             tag("JMXVOBJI1000\n"),
-            flat_map(terminated(parse_u16_str, line_ending), |count| {
-                many_m_n(count as usize, count as usize, Self::parse_single)
+            flat_map(terminated(parse_u16_str, line_ending), |c| {
+                map(count(Self::parse_single, c as usize), Vec::from)
             }),
         )(i)
     }
 }
 
 impl ObjectIfo {
-    fn parse_single<'i>(i: &'i str) -> IResult<&'i str, Self> {
-        terminated(
-            map(
-                tuple((
-                    parse_u16_str,
-                    preceded(multispace1, parse_u32_hex_str),
-                    preceded(multispace1, parse_quoted_path_buf),
-                )),
-                ttr_closure! {
-                    ObjectIfo {
-                        index, flag, path
-                    }
-                },
+    fn parse_single(i: &str) -> IResult<&str, Self> {
+        context(
+            "ObjectIfo",
+            terminated(
+                map(
+                    fields!(
+                        index: parse_u16_str,
+                        flag: preceded(multispace1, parse_u32_hex_str),
+                        path: preceded(multispace1, parse_quoted_path_buf),
+                    ),
+                    ttr_closure! {
+                        ObjectIfo {
+                            index, flag, path
+                        }
+                    },
+                ),
+                line_ending,
             ),
-            line_ending,
         )(i)
     }
 }
@@ -1,4 +1,10 @@
+// the parsed formats are full of tuples of boxed slices mirroring the file layout
+#![allow(clippy::type_complexity)]
+
+pub mod dds;
 pub mod divisioninfo;
+#[cfg(feature = "gltf")]
+pub mod gltf;
 pub mod gmwpfort;
 pub mod jmxv2dti;
 
@@ -18,9 +24,26 @@ pub mod jmxvmapt;
 pub mod jmxvmfo;
 pub mod jmxvnvm;
 pub mod jmxvobji;
+pub mod navigation;
 pub mod newinterface;
+pub mod placement;
+pub mod scan;
+pub mod span;
+#[cfg(feature = "serde")]
+pub mod stats;
 
+mod detect;
+mod error;
+mod loader;
+mod math;
 mod parser_ext;
+mod region;
+mod writer_ext;
+
+pub use detect::{detect, parse_any, AnyFile, FileKind};
+pub use error::{Error, ErrorKind, Result};
+pub use loader::LoadError;
+pub use region::RegionId;
 
 pub mod enums;
 pub use enums::*;
@@ -44,3 +67,12 @@ macro_rules! tuple_to_record_closure {
     };
 }
 use tuple_to_record_closure as ttr_closure;
+
+/// A `tuple` of parsers that records the span of each element under the given field name, see
+/// [`span`].
+macro_rules! fields {
+    ($( $field:ident: $parser:expr ),+ $(,)?) => {
+        nom::sequence::tuple(($( $crate::span::field(stringify!($field), $parser), )+))
+    };
+}
+use fields;
@@ -0,0 +1,64 @@
+//! Loading the files referenced by other files through a user provided loader.
+
+use std::path::Path;
+use std::{fmt, io};
+
+use crate::error::Error;
+use crate::jmxvmapm::LayoutError;
+
+/// The error returned when a file referenced by another one could not be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The loader failed to provide the file at `path`.
+    Load { path: Box<Path>, error: io::Error },
+    /// The file at `path` could not be parsed.
+    Parse { path: Box<Path>, error: Error },
+    /// The object index `id` isn't listed in `object.ifo`.
+    UnknownObject { id: u32 },
+    /// The map mesh of a region doesn't cover the whole region.
+    MapMesh(LayoutError),
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Load { error, .. } => Some(error),
+            LoadError::Parse { error, .. } => Some(error),
+            LoadError::UnknownObject { .. } => None,
+            LoadError::MapMesh(error) => Some(error),
+        }
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Load { path, error } => {
+                write!(f, "failed to load {}: {}", path.display(), error)
+            },
+            LoadError::Parse { path, error } => {
+                write!(f, "failed to parse {}: {}", path.display(), error)
+            },
+            LoadError::UnknownObject { id } => {
+                write!(f, "object {} is not listed in object.ifo", id)
+            },
+            LoadError::MapMesh(error) => write!(f, "invalid map mesh: {}", error),
+        }
+    }
+}
+
+/// Loads the file at `path` with `loader` and parses it with `parse`.
+pub(crate) fn load<T>(
+    loader: &mut dyn FnMut(&Path) -> io::Result<Vec<u8>>,
+    path: &Path,
+    parse: impl FnOnce(&[u8]) -> crate::Result<T>,
+) -> Result<T, LoadError> {
+    let data = loader(path).map_err(|error| LoadError::Load {
+        path: path.into(),
+        error,
+    })?;
+    parse(&data).map_err(|error| LoadError::Parse {
+        path: path.into(),
+        error,
+    })
+}
@@ -0,0 +1,138 @@
+//! Just enough vector math for bone transforms, which are rigid and thus need no scale.
+
+pub(crate) type Vec3 = [f32; 3];
+/// A quaternion in `[x, y, z, w]` order.
+pub(crate) type Quat = [f32; 4];
+/// A column-major 4x4 matrix.
+pub(crate) type Mat4 = [f32; 16];
+
+pub(crate) const QUAT_IDENTITY: Quat = [0.0, 0.0, 0.0, 1.0];
+
+pub(crate) fn quat_mul(a: Quat, b: Quat) -> Quat {
+    let [ax, ay, az, aw] = a;
+    let [bx, by, bz, bw] = b;
+    [
+        aw * bx + ax * bw + ay * bz - az * by,
+        aw * by - ax * bz + ay * bw + az * bx,
+        aw * bz + ax * by - ay * bx + az * bw,
+        aw * bw - ax * bx - ay * by - az * bz,
+    ]
+}
+
+pub(crate) fn quat_conjugate([x, y, z, w]: Quat) -> Quat {
+    [-x, -y, -z, w]
+}
+
+pub(crate) fn quat_normalize(q: Quat) -> Quat {
+    let len = q.iter().map(|c| c * c).sum::<f32>().sqrt();
+    if len > f32::EPSILON {
+        q.map(|c| c / len)
+    } else {
+        QUAT_IDENTITY
+    }
+}
+
+pub(crate) fn quat_rotate(q: Quat, v: Vec3) -> Vec3 {
+    let [x, y, z, _] = quat_mul(quat_mul(q, [v[0], v[1], v[2], 0.0]), quat_conjugate(q));
+    [x, y, z]
+}
+
+/// Spherical linear interpolation along the shorter arc.
+pub(crate) fn quat_slerp(a: Quat, b: Quat, t: f32) -> Quat {
+    let mut dot = a.iter().zip(b).map(|(a, b)| a * b).sum::<f32>();
+    let b = if dot < 0.0 {
+        dot = -dot;
+        b.map(|c| -c)
+    } else {
+        b
+    };
+    let (wa, wb) = if dot > 0.9995 {
+        // nearly parallel, fall back to lerp to avoid dividing by ~0
+        (1.0 - t, t)
+    } else {
+        let theta = dot.acos();
+        let sin = theta.sin();
+        (((1.0 - t) * theta).sin() / sin, (t * theta).sin() / sin)
+    };
+    quat_normalize([0, 1, 2, 3].map(|idx| wa * a[idx] + wb * b[idx]))
+}
+
+pub(crate) fn lerp(a: Vec3, b: Vec3, t: f32) -> Vec3 {
+    [0, 1, 2].map(|idx| a[idx] + (b[idx] - a[idx]) * t)
+}
+
+/// A rotation followed by a translation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct Transform {
+    pub(crate) rotation: Quat,
+    pub(crate) translation: Vec3,
+}
+
+impl Transform {
+    /// Applies `child` first, then `self`.
+    pub(crate) fn mul(&self, child: &Transform) -> Transform {
+        let [x, y, z] = self.transform_point(child.translation);
+        Transform {
+            rotation: quat_normalize(quat_mul(self.rotation, child.rotation)),
+            translation: [x, y, z],
+        }
+    }
+
+    pub(crate) fn inverse(&self) -> Transform {
+        let rotation = quat_conjugate(self.rotation);
+        let [x, y, z] = quat_rotate(rotation, self.translation);
+        Transform {
+            rotation,
+            translation: [-x, -y, -z],
+        }
+    }
+
+    pub(crate) fn transform_point(&self, p: Vec3) -> Vec3 {
+        let [x, y, z] = quat_rotate(self.rotation, p);
+        let [tx, ty, tz] = self.translation;
+        [x + tx, y + ty, z + tz]
+    }
+
+    pub(crate) fn to_matrix(self) -> Mat4 {
+        let [x, y, z, w] = self.rotation;
+        let [tx, ty, tz] = self.translation;
+        [
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y + z * w),
+            2.0 * (x * z - y * w),
+            0.0,
+            2.0 * (x * y - z * w),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z + x * w),
+            0.0,
+            2.0 * (x * z + y * w),
+            2.0 * (y * z - x * w),
+            1.0 - 2.0 * (x * x + y * y),
+            0.0,
+            tx,
+            ty,
+            tz,
+            1.0,
+        ]
+    }
+}
+
+#[test]
+fn transform_inverse() {
+    let half = std::f32::consts::FRAC_1_SQRT_2;
+    let t = Transform {
+        rotation: [0.0, half, 0.0, half],
+        translation: [1.0, 2.0, 3.0],
+    };
+    let p = t
+        .inverse()
+        .transform_point(t.transform_point([4.0, 5.0, 6.0]));
+    assert!(p
+        .iter()
+        .zip([4.0, 5.0, 6.0])
+        .all(|(a, b)| (a - b).abs() < 1e-5));
+    let m = t.to_matrix();
+    let rotated = t.transform_point([1.0, 0.0, 0.0]);
+    assert!((m[0] + m[12] - rotated[0]).abs() < 1e-5);
+    assert!((m[2] + m[14] - rotated[2]).abs() < 1e-5);
+}
@@ -0,0 +1,378 @@
+//! Path finding over the navigation cells of [`JmxNvm`] files.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+use mint::{Vector2, Vector3};
+
+use crate::jmxvnvm::{JmxNvm, REGION_SIZE};
+use crate::region::RegionId;
+
+/// A position local to a region.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NavPosition {
+    pub region: RegionId,
+    pub x: f32,
+    pub z: f32,
+}
+
+impl NavPosition {
+    /// The position relative to the origin of the region grid, used to measure distances across
+    /// regions. Unlike [`RegionId::to_world`] this also places dungeon regions on a grid.
+    fn absolute(&self) -> (f32, f32) {
+        let region_x = f32::from(self.region.x());
+        let region_z = f32::from(self.region.y());
+        (
+            region_x * REGION_SIZE + self.x,
+            region_z * REGION_SIZE + self.z,
+        )
+    }
+
+    fn distance(&self, other: &NavPosition) -> f32 {
+        let (ax, az) = self.absolute();
+        let (bx, bz) = other.absolute();
+        (ax - bx).hypot(az - bz)
+    }
+}
+
+/// A cell of a region.
+type Node = (RegionId, usize);
+
+struct Edge {
+    to: Node,
+    /// The midpoint of the line shared by both cells.
+    via: NavPosition,
+}
+
+/// Line flag bit blocking movement from the source to the destination cell, `BlockSrc2Dst` in the
+/// community NVM documentation.
+const BLOCK_SRC_TO_DST: u8 = 0x1;
+/// Line flag bit blocking movement from the destination to the source cell, `BlockDst2Src` in the
+/// community NVM documentation.
+const BLOCK_DST_TO_SRC: u8 = 0x2;
+
+/// The cells of a set of regions connected by their cell and region links.
+pub struct NavGraph<'a> {
+    regions: HashMap<RegionId, &'a JmxNvm>,
+    edges: HashMap<Node, Vec<Edge>>,
+}
+
+impl<'a> NavGraph<'a> {
+    /// Builds the graph of the given regions.
+    ///
+    /// Links to cells that don't exist, including links to regions that aren't part of the
+    /// graph, are ignored. A line flag of `0x1` blocks the link from its source to its
+    /// destination cell and `0x2` the other way around.
+    pub fn new(regions: impl IntoIterator<Item = (RegionId, &'a JmxNvm)>) -> Self {
+        let regions = regions.into_iter().collect::<HashMap<_, _>>();
+        let exists = |(region, cell): Node| {
+            regions
+                .get(&region)
+                .is_some_and(|nvm| cell < nvm.nav_cells.len())
+        };
+        let mut edges = HashMap::<Node, Vec<Edge>>::new();
+        let mut link = |from: Node, to: Node, line_flag: u8, via: NavPosition| {
+            if !exists(from) || !exists(to) {
+                return;
+            }
+            if line_flag & BLOCK_SRC_TO_DST == 0 {
+                let via = rebase(via, to.0);
+                edges.entry(from).or_default().push(Edge { to, via });
+            }
+            if line_flag & BLOCK_DST_TO_SRC == 0 {
+                let via = rebase(via, from.0);
+                edges.entry(to).or_default().push(Edge { to: from, via });
+            }
+        };
+        for (&region, nvm) in &regions {
+            for cell_link in nvm.nav_cell_links.iter() {
+                link(
+                    (region, usize::from(cell_link.cell_source)),
+                    (region, usize::from(cell_link.cell_destination)),
+                    cell_link.line_flag,
+                    midpoint(region, cell_link.min, cell_link.max),
+                );
+            }
+            for region_link in nvm.nav_region_links.iter() {
+                // both regions may store the border, only take it from the source region
+                if region_link.region_source != region {
+                    continue;
+                }
+                link(
+                    (region, usize::from(region_link.cell_source)),
+                    (
+                        region_link.region_destination,
+                        usize::from(region_link.cell_destination),
+                    ),
+                    region_link.line_flag,
+                    midpoint(region, region_link.min, region_link.max),
+                );
+            }
+        }
+        NavGraph { regions, edges }
+    }
+
+    fn cell_at(&self, position: &NavPosition) -> Option<Node> {
+        let nvm = self.regions.get(&position.region)?;
+        let cell = nvm.nav_cell_index_at(position.x, position.z)?;
+        Some((position.region, cell))
+    }
+
+    fn cell_center(&self, (region, cell): Node) -> NavPosition {
+        let cell = &self.regions[&region].nav_cells[cell];
+        NavPosition {
+            region,
+            x: (cell.min.x + cell.max.x) / 2.0,
+            z: (cell.min.y + cell.max.y) / 2.0,
+        }
+    }
+
+    /// Finds a path between two positions in world units with A*, returning the waypoints
+    /// including both ends.
+    ///
+    /// The waypoints in between lie on the terrain of their region, see
+    /// [`find_local_path`](Self::find_local_path) for how they are chosen.
+    /// Returns `None` if either position lies outside of the world grid or the graph's cells, if
+    /// there is no path connecting them, if the path leads through a dungeon region or through a
+    /// region whose height map is incomplete.
+    pub fn find_path(&self, from: Vector3<f32>, to: Vector3<f32>) -> Option<Vec<Vector3<f32>>> {
+        let local = |world| {
+            let (region, local) = RegionId::from_world(world)?;
+            Some(NavPosition {
+                region,
+                x: local.x,
+                z: local.z,
+            })
+        };
+        let path = self.find_local_path(local(from)?, local(to)?)?;
+        let last = path.len() - 1;
+        path.iter()
+            .enumerate()
+            .map(|(idx, waypoint)| match idx {
+                0 => Some(from),
+                idx if idx == last => Some(to),
+                _ => {
+                    let y = self.regions[&waypoint.region].height_at(waypoint.x, waypoint.z)?;
+                    waypoint.region.to_world(Vector3 {
+                        x: waypoint.x,
+                        y,
+                        z: waypoint.z,
+                    })
+                },
+            })
+            .collect()
+    }
+
+    /// Finds a path between two region local positions with A*, returning the waypoints
+    /// including both ends.
+    ///
+    /// The waypoints in between are the midpoints of the crossed cell borders, relative to the
+    /// region of the cell they lead into. The path is shortest with respect to those and the cell
+    /// centres rather than the actual walkable area.
+    /// Returns `None` if either position lies outside of the graph's cells or if there is no
+    /// path connecting them.
+    pub fn find_local_path(&self, from: NavPosition, to: NavPosition) -> Option<Vec<NavPosition>> {
+        let start = self.cell_at(&from)?;
+        let goal = self.cell_at(&to)?;
+
+        let mut open = BinaryHeap::new();
+        let mut closed = HashSet::new();
+        let mut came_from = HashMap::<Node, (Node, NavPosition)>::new();
+        let mut costs = HashMap::from([(start, 0.0f32)]);
+        open.push(Candidate {
+            estimate: from.distance(&to),
+            node: start,
+        });
+        while let Some(Candidate { node, .. }) = open.pop() {
+            // a node is pushed again whenever a cheaper way to it is found, skip the stale entries
+            if !closed.insert(node) {
+                continue;
+            }
+            if node == goal {
+                let mut waypoints = vec![to];
+                let mut current = node;
+                while let Some(&(previous, via)) = came_from.get(&current) {
+                    waypoints.push(via);
+                    current = previous;
+                }
+                waypoints.push(from);
+                waypoints.reverse();
+                return Some(waypoints);
+            }
+            let position = if node == start {
+                from
+            } else {
+                self.cell_center(node)
+            };
+            let cost = costs[&node];
+            for edge in self.edges.get(&node).into_iter().flatten() {
+                if closed.contains(&edge.to) {
+                    continue;
+                }
+                let next_position = if edge.to == goal {
+                    to
+                } else {
+                    self.cell_center(edge.to)
+                };
+                let next_cost =
+                    cost + position.distance(&edge.via) + edge.via.distance(&next_position);
+                if costs.get(&edge.to).is_some_and(|&known| known <= next_cost) {
+                    continue;
+                }
+                costs.insert(edge.to, next_cost);
+                came_from.insert(edge.to, (node, edge.via));
+                open.push(Candidate {
+                    estimate: next_cost + next_position.distance(&to),
+                    node: edge.to,
+                });
+            }
+        }
+        None
+    }
+}
+
+fn midpoint(region: RegionId, min: Vector2<f32>, max: Vector2<f32>) -> NavPosition {
+    NavPosition {
+        region,
+        x: (min.x + max.x) / 2.0,
+        z: (min.y + max.y) / 2.0,
+    }
+}
+
+/// Expresses `position` relative to `region`.
+fn rebase(position: NavPosition, region: RegionId) -> NavPosition {
+    let (x, z) = position.absolute();
+    let (origin_x, origin_z) = NavPosition {
+        region,
+        x: 0.0,
+        z: 0.0,
+    }
+    .absolute();
+    NavPosition {
+        region,
+        x: x - origin_x,
+        z: z - origin_z,
+    }
+}
+
+/// An open node ordered by its estimated total cost, cheapest first.
+struct Candidate {
+    estimate: f32,
+    node: Node,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+#[test]
+fn find_path_across_regions() {
+    use crate::jmxvnvm::{NavCell, NavCellLink, NavRegionLink, HEIGHT_MAP_SIZE};
+
+    let v2 = |x, y| Vector2 { x, y };
+    let region = |id: u16, line_flag| JmxNvm {
+        nav_entries: Box::new([]),
+        nav_extra_count: 0,
+        nav_cells: Box::new([
+            NavCell {
+                min: v2(0.0, 0.0),
+                max: v2(960.0, REGION_SIZE),
+                entries: Box::new([]),
+            },
+            NavCell {
+                min: v2(960.0, 0.0),
+                max: v2(REGION_SIZE, REGION_SIZE),
+                entries: Box::new([]),
+            },
+        ]),
+        nav_region_links: Box::new([NavRegionLink {
+            min: v2(REGION_SIZE, 0.0),
+            max: v2(REGION_SIZE, REGION_SIZE),
+            line_flag: 0,
+            line_source: 0,
+            line_destination: 0,
+            cell_source: 1,
+            cell_destination: 0,
+            region_source: RegionId(id),
+            region_destination: RegionId(id + 1),
+        }]),
+        nav_cell_links: Box::new([NavCellLink {
+            min: v2(960.0, 0.0),
+            max: v2(960.0, REGION_SIZE),
+            line_flag,
+            line_source: 0,
+            line_destination: 0,
+            cell_source: 0,
+            cell_destination: 1,
+        }]),
+        texture_map: Box::new([]),
+        height_map: vec![5.0; HEIGHT_MAP_SIZE * HEIGHT_MAP_SIZE].into(),
+    };
+    let (west, east) = (region(0x0101, 0), region(0x0102, 0));
+    let from = NavPosition {
+        region: RegionId(0x0101),
+        x: 100.0,
+        z: 960.0,
+    };
+    let to = NavPosition {
+        region: RegionId(0x0102),
+        x: 1800.0,
+        z: 960.0,
+    };
+    let graph = NavGraph::new([(RegionId(0x0101), &west), (RegionId(0x0102), &east)]);
+    let path = graph.find_local_path(from, to).unwrap();
+    let expected = [
+        (0x0101, 100.0),
+        (0x0101, 960.0),
+        (0x0102, 0.0),
+        (0x0102, 960.0),
+        (0x0102, 1800.0),
+    ];
+    assert_eq!(path.len(), expected.len());
+    for (waypoint, (region, x)) in path.iter().zip(expected) {
+        assert_eq!(waypoint.region, RegionId(region));
+        assert!((waypoint.x - x).abs() < 1e-3 && (waypoint.z - 960.0).abs() < 1e-3);
+    }
+
+    let world = |x, z| Vector3 { x, y: 1.0, z };
+    let path = graph
+        .find_path(world(2020.0, 2880.0), world(5640.0, 2880.0))
+        .unwrap();
+    let expected = [
+        (2020.0, 1.0),
+        (2880.0, 5.0),
+        (3840.0, 5.0),
+        (4800.0, 5.0),
+        (5640.0, 1.0),
+    ];
+    assert_eq!(path.len(), expected.len());
+    for (waypoint, (x, y)) in path.iter().zip(expected) {
+        assert!((waypoint.x - x).abs() < 1e-3 && (waypoint.y - y).abs() < 1e-3);
+        assert!((waypoint.z - 2880.0).abs() < 1e-3);
+    }
+    assert!(graph
+        .find_path(world(2020.0, 2880.0), world(100.0, 2880.0))
+        .is_none());
+
+    // the cell link leads from cell 0 to cell 1, 0x1 only blocks that direction
+    let blocked = region(0x0102, BLOCK_SRC_TO_DST);
+    let graph = NavGraph::new([(RegionId(0x0101), &west), (RegionId(0x0102), &blocked)]);
+    assert!(graph.find_local_path(from, to).is_none());
+    assert_eq!(graph.find_local_path(to, from).unwrap().len(), 5);
+}
@@ -2,19 +2,19 @@ use std::path::Path;
 
 use nom::combinator::map;
 use nom::number::complete::{le_f32, le_u32};
-use nom::sequence::tuple;
-use nom::IResult;
 
+use crate::error::{finish, IResult, Result};
 use crate::parser_ext::flags::flags_u32;
 use crate::parser_ext::multi::parse_objects_u32;
 use crate::parser_ext::string::{fixed_path, fixed_string_64};
-use crate::NewInterfaceType;
+use crate::span::context;
+use crate::{fields, NewInterfaceType};
 
 #[cfg(feature = "serde")]
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 
 bitflags::bitflags! {
-    #[cfg_attr(feature = "serde", derive(Serialize))]
+    #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     #[cfg_attr(feature = "serde", serde(transparent))]
     pub struct NewInterfaceStyle: u32 {
         const CENTER = 256;
@@ -24,7 +24,7 @@ bitflags::bitflags! {
 }
 
 #[derive(Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NewInterface {
     pub name: Box<str>,
     pub image: Box<Path>,
@@ -74,47 +74,61 @@ pub struct NewInterface {
 }
 
 impl NewInterface {
-    pub fn parse<'a>(i: &'a [u8]) -> IResult<&'a [u8], Box<[Self]>> {
+    pub fn parse(i: &[u8]) -> Result<Box<[Self]>> {
+        finish("NewInterface", i, Self::parse_nom)
+    }
+
+    pub(crate) fn parse_nom(i: &[u8]) -> IResult<&[u8], Box<[Self]>> {
         parse_objects_u32(Self::parse_single)(i)
     }
 
+    fn parse_single(i: &[u8]) -> IResult<&[u8], Self> {
+        context("NewInterface", Self::parse_fields)(i)
+    }
+
     #[rustfmt::skip]
-    fn parse_single<'a, >(i: &'a [u8]) -> IResult<&'a [u8], Self> {
-        let (i, (name, image, background, text, description, prototype)) = tuple((
-            fixed_string_64,
-            fixed_path::<256>,
-            fixed_path::<256>,
-            fixed_path::<128>,
-            fixed_string_64,
-            fixed_string_64,
-        ))(i)?;
+    fn parse_fields(i: &[u8]) -> IResult<&[u8], Self> {
+        let (i, (name, image, background, text, description, prototype)) = fields!(
+            name: fixed_string_64,
+            image: fixed_path::<256>,
+            background: fixed_path::<256>,
+            text: fixed_path::<128>,
+            description: fixed_string_64,
+            prototype: fixed_string_64,
+        )(i)?;
         let (i,
             (
                 ty, id, parent_id, grand_parent_id, unk00, unk01, color, client_rectangle_x,
                 client_rectangle_y, client_rectangle_width, client_rectangle_height,
             ),
-        ) = tuple((
-            NewInterfaceType::parse, le_u32, le_u32, le_u32, le_u32,
-            le_u32, le_u32, le_u32, le_u32, le_u32, le_u32,
-        ))(i)?;
+        ) = fields!(
+            ty: NewInterfaceType::parse, id: le_u32, parent_id: le_u32, grand_parent_id: le_u32,
+            unk00: le_u32, unk01: le_u32, color: le_u32, client_rectangle_x: le_u32,
+            client_rectangle_y: le_u32, client_rectangle_width: le_u32,
+            client_rectangle_height: le_u32,
+        )(i)?;
         let (i,
             (
                 uv_top_left_x, uv_top_left_y, uv_top_right_x, uv_top_right_y, uv_bot_left_x,
                 uv_bot_left_y, uv_bot_right_x, uv_bot_right_y, unk02, content_id, is_root,
             ),
-        ) = tuple((
-            le_f32, le_f32, le_f32, le_f32, le_f32, le_f32, le_f32, le_f32, le_u32, le_u32,
-            map(le_u32, |int| int != 0),
-        ))(i)?;
+        ) = fields!(
+            uv_top_left_x: le_f32, uv_top_left_y: le_f32, uv_top_right_x: le_f32,
+            uv_top_right_y: le_f32, uv_bot_left_x: le_f32, uv_bot_left_y: le_f32,
+            uv_bot_right_x: le_f32, uv_bot_right_y: le_f32, unk02: le_u32, content_id: le_u32,
+            is_root: map(le_u32, |int| int != 0),
+        )(i)?;
         let (i,
             (
                 unk03, unk04, unk05, unk06, unk07, unk08, unk09,
                 unk10, unk11, unk12, unk13, unk14, unk15, style,
             ),
-        ) = tuple((
-            le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32, le_u32,
-            le_u32, le_u32, le_u32, le_u32, le_u32, flags_u32(NewInterfaceStyle::from_bits),
-        ))(i)?;
+        ) = fields!(
+            unk03: le_u32, unk04: le_u32, unk05: le_u32, unk06: le_u32, unk07: le_u32,
+            unk08: le_u32, unk09: le_u32, unk10: le_u32, unk11: le_u32, unk12: le_u32,
+            unk13: le_u32, unk14: le_u32, unk15: le_u32,
+            style: flags_u32(NewInterfaceStyle::from_bits_unchecked),
+        )(i)?;
         Ok((
             i,
             NewInterface {
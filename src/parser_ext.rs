@@ -1,14 +1,16 @@
 use mint::{Vector2, Vector3, Vector4};
 use nom::bytes::complete::{tag, take, take_till};
 use nom::character::complete::{char, digit1, hex_digit1};
-use nom::combinator::{flat_map, map, map_opt, map_res};
+use nom::combinator::{flat_map, map, map_res};
 use nom::error::ParseError;
 use nom::number::complete::{le_f32, le_u16, le_u32, le_u8};
 use nom::sequence::{delimited, preceded, tuple};
-use nom::{IResult, ToUsize};
+use nom::ToUsize;
 
 use std::path::PathBuf;
 
+use crate::error::IResult;
+
 #[allow(dead_code)]
 #[track_caller]
 pub fn dbg<'i, O: std::fmt::Debug>(
@@ -28,6 +30,25 @@ pub mod complete {
     use nom::error::{make_error, ErrorKind};
 
     use super::*;
+    use crate::error::{self, ParseError};
+
+    /// Runs `f` on the input starting at `offset`, failing instead of panicking if the offset lies
+    /// past the end of the input.
+    pub fn at<'i, O>(
+        offset: u32,
+        mut f: impl FnMut(&'i [u8]) -> IResult<&'i [u8], O>,
+    ) -> impl FnMut(&'i [u8]) -> IResult<&'i [u8], O> {
+        move |i: &'i [u8]| match i.get(offset as usize..) {
+            Some(section) => {
+                crate::span::seek(section);
+                f(section)
+            },
+            None => Err(nom::Err::Failure(ParseError::new(
+                &i[i.len()..],
+                error::ErrorKind::InvalidOffset(offset),
+            ))),
+        }
+    }
 
     pub fn take_fixed<const C: usize>(i: &[u8]) -> IResult<&[u8], [u8; C]> {
         take(C)(i).and_then(|(i, arr)| match <[u8; C]>::try_from(arr) {
@@ -98,7 +119,7 @@ pub mod string {
 
     /// Reads a u32, then reads the amount of bytes specified by the u32 and parses it as a EUC_KR string encoded string
     #[inline]
-    pub fn sized_string<'i>(i: &'i [u8]) -> IResult<&'i [u8], Box<str>> {
+    pub fn sized_string(i: &[u8]) -> IResult<&[u8], Box<str>> {
         map(flat_map(le_u32, take), |s| {
             match encoding_rs::EUC_KR.decode_without_bom_handling(s).0 {
                 Cow::Borrowed(it) => it.into(),
@@ -107,17 +128,6 @@ pub mod string {
         })(i)
     }
 
-    /// Reads a u16, then reads the amount of bytes specified by the u32 and parses it as a EUC_KR string encoded string
-    #[inline]
-    pub fn small_sized_string<'i>(i: &'i [u8]) -> IResult<&'i [u8], Box<str>> {
-        map(flat_map(le_u16, take), |s| {
-            match encoding_rs::EUC_KR.decode_without_bom_handling(s).0 {
-                Cow::Borrowed(it) => it.into(),
-                Cow::Owned(it) => it.into_boxed_str(),
-            }
-        })(i)
-    }
-
     pub fn sized_string_ref<'i>(i: &'i [u8]) -> IResult<&'i [u8], Cow<'i, str>> {
         map(flat_map(le_u32, take), |s| {
             encoding_rs::EUC_KR.decode_without_bom_handling(s).0
@@ -126,7 +136,7 @@ pub mod string {
 
     /// Reads a sized_string and turns it into a PathBuf
     #[inline]
-    pub fn sized_path<'i>(i: &'i [u8]) -> IResult<&'i [u8], Box<Path>> {
+    pub fn sized_path(i: &[u8]) -> IResult<&[u8], Box<Path>> {
         map(flat_map(le_u32, take), |s| {
             Box::from(match encoding_rs::EUC_KR.decode_without_bom_handling(s).0 {
                 Cow::Borrowed(it) => Cow::Borrowed(it.as_ref()),
@@ -135,17 +145,6 @@ pub mod string {
         })(i)
     }
 
-    /// Reads a sized_string and turns it into a PathBuf
-    #[inline]
-    pub fn small_sized_path<'i>(i: &'i [u8]) -> IResult<&'i [u8], Box<Path>> {
-        map(flat_map(le_u16, take), |s| {
-            Box::from(match encoding_rs::EUC_KR.decode_without_bom_handling(s).0 {
-                Cow::Borrowed(it) => Cow::Borrowed(it.as_ref()),
-                Cow::Owned(it) => Cow::Owned(PathBuf::from(it)),
-            })
-        })(i)
-    }
-
     #[inline]
     pub fn fixed_string<'i, const LEN: usize>(data: &'i [u8]) -> IResult<&'i [u8], Box<str>> {
         map(take(LEN), move |bytes: &'i [u8]| {
@@ -177,38 +176,32 @@ pub mod string {
     }
 
     #[inline]
-    pub fn fixed_string_64<'i>(i: &'i [u8]) -> IResult<&'i [u8], Box<str>> {
+    pub fn fixed_string_64(i: &[u8]) -> IResult<&[u8], Box<str>> {
         fixed_string::<64>(i)
     }
-
-    #[inline]
-    pub fn fixed_string_128<'i>(i: &'i [u8]) -> IResult<&'i [u8], Box<str>> {
-        fixed_string::<128>(i)
-    }
-
-    #[inline]
-    pub fn fixed_string_256<'i>(i: &'i [u8]) -> IResult<&'i [u8], Box<str>> {
-        fixed_string::<256>(i)
-    }
 }
 
 pub mod flags {
     use super::*;
 
+    /// Parses bitflags from a `u16` with the type's `from_bits_unchecked`, retaining bits without
+    /// a named flag so that newer files still parse and are written back unchanged.
     #[inline]
-    pub fn flags_u16<'i, F, T>(from_bits: F) -> impl FnMut(&'i [u8]) -> IResult<&'i [u8], T>
-    where
-        F: FnMut(u16) -> Option<T>,
-    {
-        map_opt(le_u16, from_bits)
+    pub fn flags_u16<'i, T>(
+        from_bits_unchecked: unsafe fn(u16) -> T,
+    ) -> impl FnMut(&'i [u8]) -> IResult<&'i [u8], T> {
+        // SAFETY: bitflags leaves it to the declaring crate whether bits without a flag are valid,
+        // they are for all flag types of this crate.
+        map(le_u16, move |bits| unsafe { from_bits_unchecked(bits) })
     }
 
+    /// Parses bitflags from a `u32`, see [`flags_u16`].
     #[inline]
-    pub fn flags_u32<'i, F, T>(from_bits: F) -> impl FnMut(&'i [u8]) -> IResult<&'i [u8], T>
-    where
-        F: FnMut(u32) -> Option<T>,
-    {
-        map_opt(le_u32, from_bits)
+    pub fn flags_u32<'i, T>(
+        from_bits_unchecked: unsafe fn(u32) -> T,
+    ) -> impl FnMut(&'i [u8]) -> IResult<&'i [u8], T> {
+        // SAFETY: see `flags_u16`
+        map(le_u32, move |bits| unsafe { from_bits_unchecked(bits) })
     }
 }
 
@@ -219,7 +212,7 @@ pub mod number {
 
     /// Reads a [f32; 6] array
     #[inline]
-    pub fn vector6_f32<'i>(i: &'i [u8]) -> IResult<&'i [u8], [f32; 6]> {
+    pub fn vector6_f32(i: &[u8]) -> IResult<&[u8], [f32; 6]> {
         map(
             tuple((le_f32, le_f32, le_f32, le_f32, le_f32, le_f32)),
             |t| [t.0, t.1, t.2, t.3, t.4, t.5],
@@ -228,7 +221,7 @@ pub mod number {
 
     /// Reads a Vector3<f32>
     #[inline]
-    pub fn vector4_f32<'i>(i: &'i [u8]) -> IResult<&'i [u8], Vector4<f32>> {
+    pub fn vector4_f32(i: &[u8]) -> IResult<&[u8], Vector4<f32>> {
         map(tuple((le_f32, le_f32, le_f32, le_f32)), |t| Vector4 {
             x: t.0,
             y: t.1,
@@ -239,7 +232,7 @@ pub mod number {
 
     /// Reads a Vector3<f32>
     #[inline]
-    pub fn vector3_f32<'i>(i: &'i [u8]) -> IResult<&'i [u8], Vector3<f32>> {
+    pub fn vector3_f32(i: &[u8]) -> IResult<&[u8], Vector3<f32>> {
         map(tuple((le_f32, le_f32, le_f32)), |t| Vector3 {
             x: t.0,
             y: t.1,
@@ -249,7 +242,7 @@ pub mod number {
 
     /// Reads a Vector2<f32>
     #[inline]
-    pub fn vector2_f32<'i>(i: &'i [u8]) -> IResult<&'i [u8], Vector2<f32>> {
+    pub fn vector2_f32(i: &[u8]) -> IResult<&[u8], Vector2<f32>> {
         map(tuple((le_f32, le_f32)), |t| Vector2 { x: t.0, y: t.1 })(i)
     }
 
@@ -276,9 +269,10 @@ pub mod number {
 }
 
 pub mod multi {
-    use nom::error::make_error;
-
     use super::*;
+
+    const MAX_INITIAL_CAPACITY: usize = 1024;
+
     /// Runs the `parse_fn` as many times as what the `count_fn` returns as a number.
     #[inline]
     pub fn parse_objects<'i, T, F, S, R>(
@@ -329,37 +323,48 @@ pub mod multi {
         parse_objects(le_u8, parse_fn)
     }
 
-    pub fn count<I, O, F>(f: F, count: usize) -> impl FnMut(I) -> IResult<I, Box<[O]>>
+    /// Runs `f` `count` times, recording the index of a failing element in the error context.
+    pub fn count<I, O, F>(mut f: F, count: usize) -> impl FnMut(I) -> IResult<I, Box<[O]>>
     where
-        I: Clone + PartialEq,
+        I: Clone,
         F: FnMut(I) -> IResult<I, O>,
     {
-        map(nom::multi::count(f, count), Vec::into_boxed_slice)
+        move |i: I| {
+            let mut input = i;
+            // the count usually comes straight from the file, so don't trust it for allocations
+            let mut res = Vec::with_capacity(count.min(MAX_INITIAL_CAPACITY));
+
+            for idx in 0..count {
+                match f(input.clone()) {
+                    Ok((i, o)) => {
+                        res.push(o);
+                        input = i;
+                    },
+                    Err(e) => return Err(e.map(|e| e.with_context(format!("[{}]", idx)))),
+                }
+            }
+
+            Ok((input, res.into_boxed_slice()))
+        }
     }
 
     /// Runs f count times, while passing the iteration index to f
     pub fn count_indexed<I, O, F>(mut f: F, count: usize) -> impl FnMut(I) -> IResult<I, Box<[O]>>
     where
-        I: Clone + PartialEq,
+        I: Clone,
         F: FnMut(I, usize) -> IResult<I, O>,
     {
         move |i: I| {
-            let mut input = i.clone();
-            let mut res = Vec::with_capacity(count);
+            let mut input = i;
+            let mut res = Vec::with_capacity(count.min(MAX_INITIAL_CAPACITY));
 
             for idx in 0..count {
-                let input_ = input.clone();
-                match f(input_, idx) {
+                match f(input.clone(), idx) {
                     Ok((i, o)) => {
                         res.push(o);
                         input = i;
                     },
-                    Err(nom::Err::Error(_)) => {
-                        return Err(nom::Err::Error(make_error(i, nom::error::ErrorKind::Count)));
-                    },
-                    Err(e) => {
-                        return Err(e);
-                    },
+                    Err(e) => return Err(e.map(|e| e.with_context(format!("[{}]", idx)))),
                 }
             }
 
@@ -0,0 +1,174 @@
+//! Resolving the objects placed by [`JmxMapObject`]s to their resources.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+use mint::ColumnMatrix4;
+
+use crate::detect::{parse_any, AnyFile};
+use crate::error::{Error, ErrorKind};
+use crate::jmxvbsr::JmxRes;
+use crate::jmxvcpd::JmxCompound;
+use crate::jmxvmapo::{JmxMapObject, MapObject};
+use crate::jmxvobji::ObjectIfo;
+use crate::loader::{load, LoadError};
+use crate::math::Transform;
+use crate::region::RegionId;
+
+/// The resource an `object.ifo` entry points to.
+#[derive(Clone, Debug)]
+pub enum ObjectResource {
+    Res(Arc<JmxRes>),
+    Compound(Arc<JmxCompound>),
+}
+
+/// An object of a [`JmxMapObject`] joined with its `object.ifo` entry and resource.
+#[derive(Clone, Debug)]
+pub struct PlacedObject {
+    pub unique_id: u32,
+    /// The index of the object in `object.ifo`.
+    pub id: u32,
+    pub path: Box<Path>,
+    pub resource: ObjectResource,
+    pub region: RegionId,
+    /// The transform from the object's space into world space, or into the space of its region
+    /// for dungeon regions which don't lie on the world grid.
+    ///
+    /// It consists of the position and a rotation of `theta` radians around the y axis only.
+    /// [`MapObject::scale`] isn't applied as its encoding is unknown, so objects that are scaled
+    /// in game come out at their unscaled size.
+    pub transform: ColumnMatrix4<f32>,
+}
+
+/// Joins [`JmxMapObject`]s with the `object.ifo` entries their objects refer to, loading and
+/// caching the referenced resources.
+pub struct ObjectResolver<'a, F> {
+    objects: HashMap<u16, &'a ObjectIfo>,
+    loader: F,
+    cache: HashMap<u16, ObjectResource>,
+}
+
+impl<'a, F> ObjectResolver<'a, F>
+where
+    F: FnMut(&Path) -> io::Result<Vec<u8>>,
+{
+    /// Creates a resolver for the parsed `object.ifo` entries, loading resources with `loader`.
+    pub fn new(object_ifo: &'a [ObjectIfo], loader: F) -> Self {
+        ObjectResolver {
+            objects: object_ifo.iter().map(|obj| (obj.index, obj)).collect(),
+            loader,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Resolves the objects placed by `map_object`, with a result per object so that one that
+    /// can't be resolved doesn't hide the others.
+    ///
+    /// Objects listed by several groups are only yielded once per `unique_id`. Their transforms
+    /// leave out the scale, see [`PlacedObject::transform`].
+    pub fn resolve(&mut self, map_object: &JmxMapObject) -> Vec<Result<PlacedObject, LoadError>> {
+        let mut seen = HashSet::new();
+        map_object
+            .objects
+            .iter()
+            .flat_map(|group| group.entries.iter())
+            .filter(|object| seen.insert(object.unique_id))
+            .map(|object| self.resolve_object(object))
+            .collect()
+    }
+
+    fn resolve_object(&mut self, object: &MapObject) -> Result<PlacedObject, LoadError> {
+        let ifo = u16::try_from(object.id)
+            .ok()
+            .and_then(|id| self.objects.get(&id))
+            .ok_or(LoadError::UnknownObject { id: object.id })?;
+        let resource = match self.cache.entry(ifo.index) {
+            Entry::Occupied(entry) => entry.get().clone(),
+            Entry::Vacant(entry) => {
+                let resource = load(&mut self.loader, &ifo.path, |data| match parse_any(data)? {
+                    AnyFile::JmxRes(res) => Ok(ObjectResource::Res(Arc::new(res))),
+                    AnyFile::JmxCompound(cpd) => Ok(ObjectResource::Compound(Arc::new(cpd))),
+                    _ => Err(Error::new("ObjectResource", 0, ErrorKind::UnknownFormat)),
+                })?;
+                entry.insert(resource).clone()
+            },
+        };
+        let position = object
+            .region
+            .to_world(object.position)
+            .unwrap_or(object.position);
+        let half = object.theta / 2.0;
+        let transform = Transform {
+            rotation: [0.0, half.sin(), 0.0, half.cos()],
+            translation: [position.x, position.y, position.z],
+        };
+        Ok(PlacedObject {
+            unique_id: object.unique_id,
+            id: object.id,
+            path: ifo.path.clone(),
+            resource,
+            region: object.region,
+            transform: transform.to_matrix().into(),
+        })
+    }
+}
+
+#[test]
+fn resolve_map_objects() {
+    use std::path::PathBuf;
+
+    use mint::Vector3;
+
+    use crate::jmxvmapo::MapObjectGroup;
+
+    let mut cpd = b"JMXVCPD 0101".to_vec();
+    for v in [56u32, 60, 0, 0, 0, 0, 0, 0x20002, 0, 0, 0, 0, 0] {
+        cpd.extend(v.to_le_bytes());
+    }
+    let object_ifo = [ObjectIfo {
+        index: 3,
+        flag: 0,
+        path: Path::new("res\\bldg\\house.cpd").into(),
+    }];
+    let object = |unique_id, id| MapObject {
+        id,
+        position: Vector3 {
+            x: 100.0,
+            y: 5.0,
+            z: 200.0,
+        },
+        visibility_flag: 0,
+        theta: 0.0,
+        unique_id,
+        scale: 0,
+        region: RegionId::new(1, 2),
+    };
+    let group = |entries: &[MapObject]| MapObjectGroup {
+        entries: entries.iter().map(|o| object(o.unique_id, o.id)).collect(),
+    };
+    let mut loaded = Vec::<PathBuf>::new();
+    let mut resolver = ObjectResolver::new(&object_ifo, |path: &Path| {
+        loaded.push(path.into());
+        Ok(cpd.clone())
+    });
+    // the unknown object doesn't keep the ones around it from resolving
+    let map_object = JmxMapObject {
+        objects: Box::new([
+            group(&[object(7, 3)]),
+            group(&[object(7, 3), object(9, 4), object(8, 3)]),
+        ]),
+    };
+    let placed = resolver.resolve(&map_object);
+    assert_eq!(placed.len(), 3);
+    let first = placed[0].as_ref().unwrap();
+    assert!(matches!(first.resource, ObjectResource::Compound(_)));
+    assert_eq!(first.transform.w.x, 1920.0 + 100.0);
+    assert_eq!(first.transform.w.z, 2.0 * 1920.0 + 200.0);
+    assert!(matches!(placed[1], Err(LoadError::UnknownObject { id: 4 })));
+    assert_eq!(placed[2].as_ref().unwrap().unique_id, 8);
+    drop(resolver);
+    assert_eq!(loaded.len(), 1);
+}
@@ -0,0 +1,131 @@
+use mint::{Vector2, Vector3};
+use nom::combinator::map;
+use nom::number::complete::le_u16;
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use crate::error::IResult;
+use crate::jmxvnvm::REGION_SIZE;
+
+/// The length of a region's sides in game coordinates, which are a tenth of world units.
+const GAME_REGION_SIZE: f32 = REGION_SIZE / 10.0;
+/// The sector the game coordinates originate from.
+const GAME_ORIGIN: (u8, u8) = (135, 92);
+
+/// The id of a region, made up of its x sector in the low byte and its y sector in the next
+/// seven bits.
+///
+/// Dungeons set the [`DUNGEON_FLAG`](Self::DUNGEON_FLAG) bit, their sectors don't lie on the
+/// world grid.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct RegionId(pub u16);
+
+impl RegionId {
+    pub const DUNGEON_FLAG: u16 = 0x8000;
+
+    /// The world region at the given sectors, `y` is truncated to seven bits.
+    pub fn new(x: u8, y: u8) -> Self {
+        RegionId(u16::from(y & 0x7F) << 8 | u16::from(x))
+    }
+
+    pub fn is_dungeon(self) -> bool {
+        self.0 & Self::DUNGEON_FLAG != 0
+    }
+
+    pub fn x(self) -> u8 {
+        self.0 as u8
+    }
+
+    pub fn y(self) -> u8 {
+        (self.0 >> 8) as u8 & 0x7F
+    }
+
+    /// Converts a position local to this region into world units, `None` for dungeons.
+    pub fn to_world(self, local: Vector3<f32>) -> Option<Vector3<f32>> {
+        if self.is_dungeon() {
+            return None;
+        }
+        Some(Vector3 {
+            x: f32::from(self.x()) * REGION_SIZE + local.x,
+            y: local.y,
+            z: f32::from(self.y()) * REGION_SIZE + local.z,
+        })
+    }
+
+    /// Splits a position in world units into its region and the position local to it, `None` if
+    /// it lies outside of the world grid.
+    pub fn from_world(world: Vector3<f32>) -> Option<(RegionId, Vector3<f32>)> {
+        let (x, y) = (
+            (world.x / REGION_SIZE).floor(),
+            (world.z / REGION_SIZE).floor(),
+        );
+        if !(0.0..256.0).contains(&x) || !(0.0..128.0).contains(&y) {
+            return None;
+        }
+        let local = Vector3 {
+            x: world.x - x * REGION_SIZE,
+            y: world.y,
+            z: world.z - y * REGION_SIZE,
+        };
+        Some((RegionId::new(x as u8, y as u8), local))
+    }
+
+    /// Converts a position local to this region into the coordinates displayed in game, `None`
+    /// for dungeons.
+    pub fn to_game(self, local: Vector3<f32>) -> Option<Vector2<f32>> {
+        if self.is_dungeon() {
+            return None;
+        }
+        let (origin_x, origin_y) = GAME_ORIGIN;
+        Some(Vector2 {
+            x: (f32::from(self.x()) - f32::from(origin_x)) * GAME_REGION_SIZE + local.x / 10.0,
+            y: (f32::from(self.y()) - f32::from(origin_y)) * GAME_REGION_SIZE + local.z / 10.0,
+        })
+    }
+
+    /// Splits coordinates displayed in game into their region and the position local to it at a
+    /// height of zero, `None` if they lie outside of the world grid.
+    pub fn from_game(game: Vector2<f32>) -> Option<(RegionId, Vector3<f32>)> {
+        let (origin_x, origin_y) = GAME_ORIGIN;
+        RegionId::from_world(Vector3 {
+            x: (game.x / GAME_REGION_SIZE + f32::from(origin_x)) * REGION_SIZE,
+            y: 0.0,
+            z: (game.y / GAME_REGION_SIZE + f32::from(origin_y)) * REGION_SIZE,
+        })
+    }
+
+    pub(crate) fn parse(i: &[u8]) -> IResult<&[u8], Self> {
+        map(le_u16, RegionId)(i)
+    }
+}
+
+#[test]
+fn region_coordinates() {
+    let region = RegionId(0x62A8);
+    assert_eq!((region.x(), region.y()), (0xA8, 0x62));
+    assert_eq!(RegionId::new(0xA8, 0x62), region);
+    assert!(!region.is_dungeon());
+    let dungeon = RegionId(0x80A8);
+    assert!(dungeon.is_dungeon());
+    assert_eq!(dungeon.y(), 0);
+    assert!(dungeon.to_world(Vector3::from([0.0; 3])).is_none());
+
+    let local = Vector3 {
+        x: 960.0,
+        y: 12.5,
+        z: 100.0,
+    };
+    let world = region.to_world(local).unwrap();
+    assert_eq!(RegionId::from_world(world), Some((region, local)));
+    let game = region.to_game(local).unwrap();
+    assert_eq!(
+        (game.x, game.y),
+        ((168.0 - 135.0) * 192.0 + 96.0, (98.0 - 92.0) * 192.0 + 10.0)
+    );
+    let (from_game, game_local) = RegionId::from_game(game).unwrap();
+    assert_eq!(from_game, region);
+    assert!((game_local.x - local.x).abs() < 1e-2 && (game_local.z - local.z).abs() < 1e-2);
+}
@@ -0,0 +1,310 @@
+//! Scanning a corpus of extracted client files to track how well the parsers cope with it.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+
+use crate::detect::{detect, parse_any, FileKind};
+use crate::error::Error;
+
+/// The bytes shown around a failure by [`hexdump`], in rows of 16 bytes before and after the
+/// failing row.
+const HEXDUMP_CONTEXT_ROWS: usize = 2;
+
+/// The outcome of scanning a set of files.
+///
+/// All collections are ordered, so the serialized reports of two runs over the same corpus can be
+/// diffed directly, or compared with [`ScanReport::diff`].
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ScanReport {
+    /// The number of files that were looked at, including empty and undetected ones.
+    pub files: usize,
+    /// The number of files that were skipped for being empty.
+    pub empty: usize,
+    /// The number of files no format was detected for, by lowercased extension.
+    pub undetected: BTreeMap<String, usize>,
+    /// The number of files with a `JMXV` magic whose version isn't supported, by magic.
+    pub unsupported: BTreeMap<String, usize>,
+    pub formats: BTreeMap<FileKind, FormatStats>,
+    /// The files and directories that couldn't be read, with the error.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub io_errors: BTreeMap<PathBuf, String>,
+}
+
+/// The results of parsing the files of one format.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FormatStats {
+    pub parsed: usize,
+    /// The failures grouped by their [location](failure_location).
+    pub failures: BTreeMap<String, FailureGroup>,
+}
+
+impl FormatStats {
+    pub fn failed(&self) -> usize {
+        self.failures.values().map(|group| group.files.len()).sum()
+    }
+
+    /// The share of the files that were parsed successfully, from `0.0` to `1.0`.
+    pub fn success_rate(&self) -> f64 {
+        let total = self.parsed + self.failed();
+        if total == 0 {
+            return 1.0;
+        }
+        self.parsed as f64 / total as f64
+    }
+}
+
+/// The files that failed to parse at the same location.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FailureGroup {
+    /// The failing files and the offsets they failed at.
+    pub files: BTreeMap<PathBuf, usize>,
+    /// A [`hexdump`] around the failure of the first file added to the group.
+    pub hexdump: String,
+}
+
+/// The files whose results changed between two scans.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScanDiff {
+    /// Files that failed in the baseline but no longer do.
+    pub fixed: Vec<PathBuf>,
+    /// Files that fail now but didn't in the baseline, either because they parsed or because
+    /// they weren't part of it.
+    pub broken: Vec<PathBuf>,
+}
+
+impl ScanReport {
+    /// Scans all files below `root`, recording their paths relative to it.
+    ///
+    /// Files and directories below `root` that can't be read are recorded in
+    /// [`io_errors`](Self::io_errors) and skipped, only failing to read `root` itself is an
+    /// error.
+    pub fn scan_dir(root: &Path) -> io::Result<ScanReport> {
+        let mut report = ScanReport::default();
+        let mut dirs = vec![root.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            let relative = dir.strip_prefix(root).unwrap_or(&dir);
+            let read_dir = match fs::read_dir(&dir) {
+                Ok(read_dir) => read_dir,
+                Err(error) if dir == root => return Err(error),
+                Err(error) => {
+                    report.io_errors.insert(relative.into(), error.to_string());
+                    continue;
+                },
+            };
+            let mut entries = Vec::new();
+            for entry in read_dir {
+                match entry {
+                    Ok(entry) => entries.push(entry.path()),
+                    Err(error) => {
+                        report.io_errors.insert(relative.into(), error.to_string());
+                    },
+                }
+            }
+            entries.sort();
+            for path in entries {
+                let relative = path.strip_prefix(root).unwrap_or(&path);
+                if path.is_dir() {
+                    dirs.push(path);
+                    continue;
+                }
+                match fs::read(&path) {
+                    Ok(data) => report.add(relative, &data),
+                    Err(error) => {
+                        report.io_errors.insert(relative.into(), error.to_string());
+                    },
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Detects and parses `data`, recording the result under `path`.
+    ///
+    /// A parser panicking is recorded as a failure at offset 0 rather than unwinding.
+    pub fn add(&mut self, path: &Path, data: &[u8]) {
+        self.files += 1;
+        if data.is_empty() {
+            self.empty += 1;
+            return;
+        }
+        let kind = match detect(data) {
+            Some(kind) => kind,
+            None if data.starts_with(b"JMXV") => {
+                let magic = String::from_utf8_lossy(&data[..data.len().min(12)]);
+                *self.unsupported.entry(magic.into_owned()).or_default() += 1;
+                return;
+            },
+            None => {
+                let extension = path
+                    .extension()
+                    .map(|ext| ext.to_string_lossy().to_lowercase())
+                    .unwrap_or_default();
+                *self.undetected.entry(extension).or_default() += 1;
+                return;
+            },
+        };
+        let stats = self.formats.entry(kind).or_default();
+        let (location, offset) = match panic::catch_unwind(AssertUnwindSafe(|| parse_any(data))) {
+            Ok(Ok(_)) => {
+                stats.parsed += 1;
+                return;
+            },
+            Ok(Err(e)) => (failure_location(&e), e.offset()),
+            Err(_) => (format!("{:?}: panicked", kind), 0),
+        };
+        let group = stats.failures.entry(location).or_default();
+        if group.files.is_empty() {
+            group.hexdump = hexdump(data, offset);
+        }
+        group.files.insert(path.to_path_buf(), offset);
+    }
+
+    fn failing_files(&self) -> BTreeSet<&Path> {
+        self.formats
+            .values()
+            .flat_map(|stats| stats.failures.values())
+            .flat_map(|group| group.files.keys())
+            .map(PathBuf::as_path)
+            .collect()
+    }
+
+    /// Compares the failing files of this report against those of an earlier `baseline`.
+    pub fn diff(&self, baseline: &ScanReport) -> ScanDiff {
+        let now = self.failing_files();
+        let before = baseline.failing_files();
+        ScanDiff {
+            fixed: before.difference(&now).map(|&path| path.into()).collect(),
+            broken: now.difference(&before).map(|&path| path.into()).collect(),
+        }
+    }
+}
+
+/// Describes where a parse failed independently of the file, so that failures with the same
+/// cause end up in the same group: the error without its offset and with element indices
+/// erased, e.g. `JmxBMesh > NavMesh > outlines[] > NavOutline: unexpected EOF`.
+pub fn failure_location(error: &Error) -> String {
+    let mut location = error.format().to_owned();
+    for ctx in error.context() {
+        if ctx.starts_with('[') {
+            location.push_str("[]");
+        } else {
+            location.push_str(" > ");
+            location.push_str(ctx);
+        }
+    }
+    let _ = write!(location, ": {}", error.kind());
+    location
+}
+
+/// Renders the rows of `data` around `offset` as a hexdump, marking the byte at `offset`.
+///
+/// The offsets of errors in the text formats are relative to the decoded text, they only line up
+/// with the raw bytes for ASCII content.
+pub fn hexdump(data: &[u8], offset: usize) -> String {
+    let row = offset / 16;
+    let first = row.saturating_sub(HEXDUMP_CONTEXT_ROWS) * 16;
+    let last = ((row + HEXDUMP_CONTEXT_ROWS + 1) * 16).min(data.len());
+    let mut out = String::new();
+    for (idx, chunk) in data[first.min(last)..last].chunks(16).enumerate() {
+        let start = first + idx * 16;
+        let _ = write!(out, "{:08X} ", start);
+        for col in 0..16 {
+            match chunk.get(col) {
+                Some(byte) => {
+                    let _ = write!(out, " {:02X}", byte);
+                },
+                None => out.push_str("   "),
+            }
+        }
+        out.push_str("  |");
+        out.extend(chunk.iter().map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '.'
+            }
+        }));
+        out.push_str("|\n");
+        if (start..start + chunk.len()).contains(&offset) {
+            let col = offset - start;
+            let _ = writeln!(out, "{:9}{:width$}^^", "", "", width = 1 + col * 3);
+        }
+    }
+    if offset >= data.len() {
+        let _ = writeln!(out, "{:08X}  <end of input>", data.len());
+    }
+    out
+}
+
+#[test]
+fn scan_groups_failures() {
+    let mut truncated_mat = b"JMXVBMT 0102\x02\0\0\0\x04\0\0\0".to_vec();
+    let mut report = ScanReport::default();
+    report.add(Path::new("a.bmt"), &truncated_mat);
+    truncated_mat.extend(b"ab");
+    report.add(Path::new("b.bmt"), &truncated_mat);
+    report.add(Path::new("c.txt"), b"hello");
+    report.add(Path::new("d.bms"), b"JMXVBMS 0200");
+    report.add(Path::new("e"), b"");
+
+    assert_eq!((report.files, report.empty), (5, 1));
+    assert_eq!(report.undetected["txt"], 1);
+    assert_eq!(report.unsupported["JMXVBMS 0200"], 1);
+    let stats = &report.formats[&FileKind::JmxMat];
+    assert_eq!((stats.parsed, stats.failed()), (0, 2));
+    let group = &stats.failures["JmxMat > materials[] > Material: unexpected EOF"];
+    assert_eq!(group.files[Path::new("a.bmt")], 0x14);
+    assert!(group.hexdump.starts_with("00000000  4A 4D 58 56"));
+    assert!(group.hexdump.ends_with("00000014  <end of input>\n"));
+
+    let mut fixed = report.clone();
+    fixed.formats.clear();
+    assert_eq!(
+        fixed.diff(&report),
+        ScanDiff {
+            fixed: vec!["a.bmt".into(), "b.bmt".into()],
+            broken: vec![],
+        }
+    );
+}
+
+#[test]
+fn hexdump_marks_offset() {
+    let data = (0..40).collect::<Vec<u8>>();
+    let dump = hexdump(&data, 0x12);
+    let lines = dump.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[1].starts_with("00000010  10 11 12"));
+    assert_eq!(lines[2].find("^^"), lines[1].find("12"));
+}
+
+#[cfg(unix)]
+#[test]
+fn scan_dir_records_io_errors() {
+    let root = std::env::temp_dir().join(format!("sr_formats_scan_{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("sub")).unwrap();
+    fs::write(root.join("sub").join("a.txt"), b"hello").unwrap();
+    // a dangling link is listed but can't be read
+    std::os::unix::fs::symlink(root.join("missing"), root.join("broken.bms")).unwrap();
+
+    let report = ScanReport::scan_dir(&root);
+    fs::remove_dir_all(&root).unwrap();
+    let report = report.unwrap();
+    assert_eq!(report.files, 1);
+    assert_eq!(report.undetected["txt"], 1);
+    assert_eq!(
+        report.io_errors.keys().collect::<Vec<_>>(),
+        [Path::new("broken.bms")]
+    );
+    assert!(ScanReport::scan_dir(&root).is_err());
+}
@@ -0,0 +1,369 @@
+//! Recording the byte ranges the parsers read their structs and fields from.
+//!
+//! Recording is opt-in through [`record`]. Every struct wrapped in a parser `context` records its
+//! span along with each of its fields. Outside of [`record`] the fields skip the bookkeeping, so
+//! plain parsing doesn't pay for it. Fields named `unk*` or `*_unk*` are considered unknown.
+
+use std::cell::{Cell, RefCell};
+use std::fmt::Write;
+use std::ops::Range;
+
+use nom::AsBytes;
+
+use crate::error::IResult;
+
+/// The byte range a struct or field was read from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    pub range: Range<usize>,
+    /// The name of the field, or of the struct's type.
+    pub name: &'static str,
+    /// The number of spans enclosing this one.
+    pub depth: usize,
+}
+
+impl Span {
+    /// Whether the field is named `unk*` or `*_unk*`.
+    pub fn is_unknown(&self) -> bool {
+        self.name.starts_with("unk") || self.name.contains("_unk")
+    }
+}
+
+/// How the bytes of a recorded input were read.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ByteClass {
+    /// The innermost span covering the byte is a known field or struct.
+    Known,
+    /// The innermost span covering the byte is an unknown field, see [`Span::is_unknown`].
+    Unknown,
+    /// No parser read the byte.
+    Unread,
+}
+
+struct Recorder {
+    /// The address and length of the recorded input, spans outside of it are dropped.
+    base: usize,
+    len: usize,
+    /// The fields that are currently being parsed, innermost last.
+    open: Vec<OpenField>,
+    spans: Vec<Span>,
+}
+
+struct OpenField {
+    /// The address the span starts at.
+    start: usize,
+    /// The number of spans recorded before the field was entered.
+    mark: usize,
+    /// Whether the field has jumped to a section with [`seek`].
+    seeked: bool,
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<Recorder>> = const { RefCell::new(None) };
+    /// Whether `RECORDER` is set, so that parsing without recording doesn't have to borrow it.
+    static RECORDING: Cell<bool> = const { Cell::new(false) };
+}
+
+fn recording() -> bool {
+    RECORDING.with(Cell::get)
+}
+
+/// Restores the previous recorder, also when the parser panics.
+struct RecorderGuard(Option<Recorder>);
+
+impl Drop for RecorderGuard {
+    fn drop(&mut self) {
+        RECORDING.with(|recording| recording.set(self.0.is_some()));
+        RECORDER.with(|recorder| *recorder.borrow_mut() = self.0.take());
+    }
+}
+
+/// Runs `parse` on `data`, recording the spans read from `data`, sorted by their start with
+/// enclosing spans first.
+///
+/// Spans are only recorded for the binary formats, the text formats are parsed from a decoded
+/// copy of the input.
+pub fn record<'i, T>(data: &'i [u8], parse: impl FnOnce(&'i [u8]) -> T) -> (T, Vec<Span>) {
+    let recorder = Recorder {
+        base: data.as_ptr() as usize,
+        len: data.len(),
+        open: Vec::new(),
+        spans: Vec::new(),
+    };
+    let previous = RECORDER.with(|r| r.borrow_mut().replace(recorder));
+    let guard = RecorderGuard(previous);
+    RECORDING.with(|recording| recording.set(true));
+    let output = parse(data);
+    let recorder = RECORDER.with(|r| r.borrow_mut().take());
+    drop(guard);
+    let mut spans = recorder.map(|r| r.spans).unwrap_or_default();
+    spans.sort_by_key(|span| (span.range.start, usize::MAX - span.range.end, span.depth));
+    (output, spans)
+}
+
+/// Runs `f`, recording the span of its input it consumed under `name` if recording is enabled.
+///
+/// Spans recorded by a failing parser are discarded, so branches that were backtracked out of
+/// leave no trace.
+pub(crate) fn field<I, O>(
+    name: &'static str,
+    mut f: impl FnMut(I) -> IResult<I, O>,
+) -> impl FnMut(I) -> IResult<I, O>
+where
+    I: AsBytes + Clone,
+{
+    move |i: I| {
+        if !recording() {
+            return f(i);
+        }
+        let start = i.as_bytes().as_ptr() as usize;
+        let recording = RECORDER.with(|r| {
+            let mut recorder = r.borrow_mut();
+            let Some(recorder) = recorder.as_mut() else {
+                return false;
+            };
+            let mark = recorder.spans.len();
+            recorder.open.push(OpenField {
+                start,
+                mark,
+                seeked: false,
+            });
+            true
+        });
+        if !recording {
+            return f(i);
+        }
+        let res = f(i);
+        RECORDER.with(|r| {
+            let mut recorder = r.borrow_mut();
+            let Some(recorder) = recorder.as_mut() else {
+                return;
+            };
+            let Some(open) = recorder.open.pop() else {
+                return;
+            };
+            match &res {
+                Ok((rest, _)) => {
+                    let end = rest.as_bytes().as_ptr() as usize;
+                    let (base, len) = (recorder.base, recorder.len);
+                    if base <= open.start && open.start <= end && end <= base + len {
+                        recorder.spans.push(Span {
+                            range: open.start - base..end - base,
+                            name,
+                            depth: recorder.open.len(),
+                        });
+                    }
+                },
+                Err(_) => recorder.spans.truncate(open.mark),
+            }
+        });
+        res
+    }
+}
+
+/// Notes that the innermost field is about to parse the section starting at `section`.
+///
+/// Fields like `context("faces", at(header.face, ..))` are entered with the whole input, so if
+/// nothing was recorded for the field yet, its span is moved to start at the section instead.
+pub(crate) fn seek(section: &[u8]) {
+    if !recording() {
+        return;
+    }
+    RECORDER.with(|r| {
+        let mut recorder = r.borrow_mut();
+        let Some(recorder) = recorder.as_mut() else {
+            return;
+        };
+        let recorded = recorder.spans.len();
+        if let Some(open) = recorder.open.last_mut() {
+            if !open.seeked && open.mark == recorded {
+                open.start = section.as_ptr() as usize;
+                open.seeked = true;
+            }
+        }
+    });
+}
+
+/// [`nom::error::context`] that also records the span of the struct.
+pub(crate) fn context<I, O>(
+    name: &'static str,
+    f: impl FnMut(I) -> IResult<I, O>,
+) -> impl FnMut(I) -> IResult<I, O>
+where
+    I: AsBytes + Clone,
+{
+    field(name, nom::error::context(name, f))
+}
+
+/// Classifies every byte of an input of `len` bytes by the innermost of `spans` covering it.
+pub fn classify(len: usize, spans: &[Span]) -> Vec<ByteClass> {
+    let mut classes = vec![(ByteClass::Unread, None); len];
+    for span in spans {
+        let class = if span.is_unknown() {
+            ByteClass::Unknown
+        } else {
+            ByteClass::Known
+        };
+        for entry in &mut classes[span.range.clone()] {
+            if entry.1.is_none_or(|depth| depth <= span.depth) {
+                *entry = (class, Some(span.depth));
+            }
+        }
+    }
+    classes.into_iter().map(|(class, _)| class).collect()
+}
+
+/// The ranges of bytes no parser read.
+pub fn gaps(len: usize, spans: &[Span]) -> Vec<Range<usize>> {
+    let classes = classify(len, spans);
+    let mut gaps = Vec::<Range<usize>>::new();
+    for (idx, class) in classes.into_iter().enumerate() {
+        if class != ByteClass::Unread {
+            continue;
+        }
+        match gaps.last_mut() {
+            Some(gap) if gap.end == idx => gap.end += 1,
+            _ => gaps.push(idx..idx + 1),
+        }
+    }
+    gaps
+}
+
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+/// Renders `data` as a hexdump coloured with ANSI escapes, green for known, yellow for unknown and
+/// red for unread bytes, followed by the list of spans and gaps.
+pub fn annotated_hexdump(data: &[u8], spans: &[Span]) -> String {
+    let classes = classify(data.len(), spans);
+    let colour = |class| match class {
+        ByteClass::Known => GREEN,
+        ByteClass::Unknown => YELLOW,
+        ByteClass::Unread => RED,
+    };
+    let mut out = String::new();
+    for (row, chunk) in data.chunks(16).enumerate() {
+        let start = row * 16;
+        let classes = &classes[start..start + chunk.len()];
+        let _ = write!(out, "{:08X} ", start);
+        let mut current = None;
+        for (byte, &class) in chunk.iter().zip(classes) {
+            if current.replace(class) != Some(class) {
+                out.push_str(colour(class));
+            }
+            let _ = write!(out, " {:02X}", byte);
+        }
+        out.push_str(RESET);
+        out.push_str(&"   ".repeat(16 - chunk.len()));
+        out.push_str("  |");
+        let mut current = None;
+        for (&byte, &class) in chunk.iter().zip(classes) {
+            if current.replace(class) != Some(class) {
+                out.push_str(colour(class));
+            }
+            out.push(if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            });
+        }
+        out.push_str(RESET);
+        out.push_str("|\n");
+    }
+    out.push('\n');
+    for span in spans {
+        let class = if span.is_unknown() {
+            ByteClass::Unknown
+        } else {
+            ByteClass::Known
+        };
+        let _ = writeln!(
+            out,
+            "{:08X}..{:08X} {:indent$}{}{}{}",
+            span.range.start,
+            span.range.end,
+            "",
+            colour(class),
+            span.name,
+            RESET,
+            indent = span.depth * 2
+        );
+    }
+    for gap in gaps(data.len(), spans) {
+        let _ = writeln!(
+            out,
+            "{:08X}..{:08X} {}unread{}",
+            gap.start, gap.end, RED, RESET
+        );
+    }
+    out
+}
+
+#[test]
+fn record_header_fields() {
+    use crate::jmxvbsr::JmxResHeader;
+
+    let mut data = b"JMXVRES 0109".to_vec();
+    for v in [0u32; 13] {
+        data.extend(v.to_le_bytes());
+    }
+    data.extend(0x20000u32.to_le_bytes());
+    data.extend(4u32.to_le_bytes());
+    data.extend(b"name");
+    data.extend([0; 8]);
+    data.extend(b"tail");
+    let (res, spans) = record(&data, JmxResHeader::parse);
+    assert!(res.is_ok());
+    assert_eq!(spans[0].name, "JmxResHeader");
+    assert_eq!(spans[0].range, 0..data.len() - 4);
+    let unk0 = spans.iter().find(|span| span.name == "unk0").unwrap();
+    assert_eq!((unk0.range.clone(), unk0.depth), (0x2C..0x30, 1));
+    assert_eq!(gaps(data.len(), &spans), vec![data.len() - 4..data.len()]);
+    let classes = classify(data.len(), &spans);
+    assert_eq!(classes[0x2C], ByteClass::Unknown);
+    assert_eq!(classes[0x0C], ByteClass::Known);
+
+    // sections read with `at` start at their offset
+    let mut cpd = b"JMXVCPD 0101".to_vec();
+    for v in [56u32, 60, 0, 0, 0, 0, 0, 0x20002, 0, 0, 0, 0, 0] {
+        cpd.extend(v.to_le_bytes());
+    }
+    let (res, spans) = record(&cpd, crate::jmxvcpd::JmxCompound::parse);
+    assert!(res.is_ok());
+    let paths = spans.iter().find(|span| span.name == "resource_paths");
+    assert_eq!(paths.unwrap().range, 60..64);
+
+    // failed parses leave no spans behind and recording is scoped to `record`
+    let (res, spans) = record(&data[..20], JmxResHeader::parse);
+    assert!(res.is_err() && spans.is_empty());
+    assert!(RECORDER.with(|r| r.borrow().is_none()));
+}
+
+#[test]
+fn record_unknown_fields() {
+    use crate::jmxvbms::JmxBMeshHeader;
+
+    let mut data = b"JMXVBMS 0110".to_vec();
+    for v in 0u32..15 {
+        data.extend(v.to_le_bytes());
+    }
+    for name in [&b"mesh"[..], b"mat"] {
+        data.extend((name.len() as u32).to_le_bytes());
+        data.extend(name);
+    }
+    data.extend(0u32.to_le_bytes());
+    let (res, spans) = record(&data, JmxBMeshHeader::parse);
+    assert!(res.is_ok());
+    let classes = classify(data.len(), &spans);
+    // vertex, unk0 and unk4 of the header
+    assert_eq!(classes[12], ByteClass::Known);
+    assert!(classes[48..52].iter().all(|&c| c == ByteClass::Unknown));
+    assert!(classes[68..72].iter().all(|&c| c == ByteClass::Unknown));
+    assert_eq!(classes[72], ByteClass::Known);
+    assert!(classes[data.len() - 4..]
+        .iter()
+        .all(|&c| c == ByteClass::Unknown));
+    assert!(gaps(data.len(), &spans).is_empty());
+}
@@ -0,0 +1,736 @@
+//! Statistics over the values of the unknown fields of parsed files.
+//!
+//! Files are walked through their [`Serialize`] implementations, every scalar is addressed by the
+//! path of fields leading to it, e.g. `JmxRes.header.unk0`. Elements of sequences share the
+//! path of their sequence with a `[]` suffix, tuple elements are addressed by their position and
+//! enum variants by their name, e.g. `JmxRes.mesh_paths[].1`.
+
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use serde::ser::{self, Serialize};
+
+/// The number of distinct values kept per field, further values are only counted.
+const MAX_DISTINCT: usize = 256;
+
+/// A scalar value of a field.
+///
+/// None of the formats store doubles, so floats are kept as `f32`.
+#[derive(Clone, Debug)]
+pub enum Value {
+    /// A `None` or unit value, also used for fields that weren't present in a file.
+    None,
+    Bool(bool),
+    Int(i128),
+    Float(f32),
+    Str(Box<str>),
+}
+
+impl Value {
+    fn rank(&self) -> u8 {
+        match self {
+            Value::None => 0,
+            Value::Bool(_) => 1,
+            Value::Int(_) => 2,
+            Value::Float(_) => 3,
+            Value::Str(_) => 4,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Bool(b) => Some(f64::from(u8::from(b))),
+            Value::Int(i) => Some(i as f64),
+            Value::Float(f) => Some(f64::from(f)),
+            Value::None | Value::Str(_) => None,
+        }
+    }
+
+    /// Guesses what a value is from its bits.
+    pub fn shape(&self) -> Shape {
+        match *self {
+            Value::None | Value::Str(_) => Shape::Other,
+            Value::Bool(false) | Value::Int(0) | Value::Float(0.0) => Shape::Zero,
+            Value::Bool(true) => Shape::Flag,
+            Value::Float(_) => Shape::Float,
+            Value::Int(i) if i > 0 && i.count_ones() == 1 => Shape::Flag,
+            Value::Int(i) if i.unsigned_abs() < 0x1_0000 => Shape::Int,
+            Value::Int(i) => {
+                // integers of up to 32 bits whose bits make a plausible float
+                let bits = u32::try_from(i)
+                    .ok()
+                    .or_else(|| i32::try_from(i).ok().map(|i| i as u32));
+                match bits.map(f32::from_bits) {
+                    Some(f) if f.is_normal() && (1e-4..1e6).contains(&f.abs()) => Shape::Float,
+                    _ => Shape::Other,
+                }
+            },
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Value {}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+            (Value::Int(a), Value::Int(b)) => a.cmp(b),
+            (Value::Float(a), Value::Float(b)) => a.total_cmp(b),
+            (Value::Str(a), Value::Str(b)) => a.cmp(b),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::None => f.write_str("none"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(i) if *i >= 0 => write!(f, "{} ({:#X})", i, i),
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Float(x) => write!(f, "{}", x),
+            Value::Str(s) => write!(f, "{:?}", s),
+        }
+    }
+}
+
+/// What the bits of a value look like.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Shape {
+    Zero,
+    /// A single set bit.
+    Flag,
+    /// An integer below `0x10000` in magnitude.
+    Int,
+    /// A float, or an integer whose bits make a float of reasonable magnitude.
+    Float,
+    Other,
+}
+
+/// The distribution of the values of one field.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FieldStats {
+    /// The number of values seen.
+    pub count: usize,
+    /// The number of files the field was seen in.
+    pub files: usize,
+    /// The distinct values and how often they were seen, limited to the first 256 values.
+    pub distinct: BTreeMap<Value, usize>,
+    /// Whether there were more distinct values than kept in `distinct`.
+    pub truncated: bool,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    /// How many values had each shape.
+    pub shapes: BTreeMap<Shape, usize>,
+}
+
+impl FieldStats {
+    fn add(&mut self, value: Value) {
+        self.count += 1;
+        if let Some(x) = value.as_f64() {
+            self.min = Some(self.min.map_or(x, |min| min.min(x)));
+            self.max = Some(self.max.map_or(x, |max| max.max(x)));
+        }
+        *self.shapes.entry(value.shape()).or_default() += 1;
+        if let Some(count) = self.distinct.get_mut(&value) {
+            *count += 1;
+        } else if self.distinct.len() < MAX_DISTINCT {
+            self.distinct.insert(value, 1);
+        } else {
+            self.truncated = true;
+        }
+    }
+
+    /// The shape all values of the field are consistent with, `None` if they are mixed.
+    ///
+    /// Fields that are always zero are [`Shape::Zero`], fields that are zero or a single bit are
+    /// [`Shape::Flag`].
+    pub fn guess(&self) -> Option<Shape> {
+        let shapes = self.shapes.keys().copied().collect::<BTreeSet<_>>();
+        let within = |allowed: &[Shape]| shapes.iter().all(|shape| allowed.contains(shape));
+        if within(&[Shape::Zero]) {
+            Some(Shape::Zero)
+        } else if within(&[Shape::Zero, Shape::Flag]) {
+            Some(Shape::Flag)
+        } else if within(&[Shape::Zero, Shape::Flag, Shape::Int]) {
+            Some(Shape::Int)
+        } else if within(&[Shape::Zero, Shape::Float]) {
+            Some(Shape::Float)
+        } else {
+            None
+        }
+    }
+}
+
+/// How often pairs of values of two fields were seen in the same file.
+///
+/// Every file contributes each combination of the distinct values both fields had in it once,
+/// fields missing from a file count as [`Value::None`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Contingency {
+    pub pairs: BTreeMap<(Value, Value), usize>,
+}
+
+impl Contingency {
+    /// Collapses the second field to whether it was present, i.e. not `None`.
+    pub fn by_presence(&self) -> BTreeMap<(Value, bool), usize> {
+        let mut table = BTreeMap::new();
+        for ((a, b), count) in &self.pairs {
+            *table
+                .entry((a.clone(), !matches!(b, Value::None)))
+                .or_default() += count;
+        }
+        table
+    }
+}
+
+/// Collects the values of all fields named `unk*`, or `*_unk*`, over a set of files.
+///
+/// Pairs of fields to correlate, like `JmxRes.header.unk0` against the optional
+/// `JmxRes.mesh_paths[].1`, have to be registered with [`correlate`](Self::correlate) before
+/// adding the files with [`add`](Self::add).
+#[derive(Clone, Debug, Default)]
+pub struct UnknownStats {
+    pub files: usize,
+    fields: BTreeMap<String, FieldStats>,
+    correlations: BTreeMap<(String, String), Contingency>,
+}
+
+impl UnknownStats {
+    pub fn new() -> Self {
+        UnknownStats::default()
+    }
+
+    /// Starts tracking how the values of the fields at the paths `a` and `b` relate, neither has
+    /// to be unknown. Only files added afterwards are taken into account.
+    pub fn correlate(&mut self, a: &str, b: &str) {
+        self.correlations
+            .entry((a.to_owned(), b.to_owned()))
+            .or_default();
+    }
+
+    /// Adds the values of a parsed file, usually an [`AnyFile`](crate::AnyFile).
+    pub fn add<T: Serialize + ?Sized>(&mut self, file: &T) {
+        self.files += 1;
+        let mut tracked = self
+            .correlations
+            .keys()
+            .flat_map(|(a, b)| [a, b])
+            .map(|path| (path.clone(), BTreeSet::new()))
+            .collect::<BTreeMap<_, _>>();
+        let mut seen = BTreeSet::new();
+        let fields = &mut self.fields;
+        let mut walker = Walker {
+            path: String::new(),
+            visit: |path: &str, value: Value| {
+                if let Some(values) = tracked.get_mut(path) {
+                    if values.len() < MAX_DISTINCT {
+                        values.insert(value.clone());
+                    }
+                }
+                if is_unknown(path) {
+                    let stats = fields.entry(path.to_owned()).or_default();
+                    if seen.insert(path.to_owned()) {
+                        stats.files += 1;
+                    }
+                    stats.add(value);
+                }
+            },
+        };
+        // the walker itself never fails
+        let _ = file.serialize(&mut walker);
+
+        let missing = BTreeSet::from([Value::None]);
+        for ((a, b), table) in &mut self.correlations {
+            let a_values = Some(&tracked[a])
+                .filter(|v| !v.is_empty())
+                .unwrap_or(&missing);
+            let b_values = Some(&tracked[b])
+                .filter(|v| !v.is_empty())
+                .unwrap_or(&missing);
+            for a in a_values {
+                for b in b_values {
+                    *table.pairs.entry((a.clone(), b.clone())).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    /// The statistics of all unknown fields by path.
+    pub fn fields(&self) -> &BTreeMap<String, FieldStats> {
+        &self.fields
+    }
+
+    pub fn field(&self, path: &str) -> Option<&FieldStats> {
+        self.fields.get(path)
+    }
+
+    /// The contingency table of two fields registered with [`correlate`](Self::correlate).
+    pub fn correlation(&self, a: &str, b: &str) -> Option<&Contingency> {
+        self.correlations.get(&(a.to_owned(), b.to_owned()))
+    }
+}
+
+/// Whether any field on the path is named like an unknown one.
+fn is_unknown(path: &str) -> bool {
+    path.split(['.', '['])
+        .any(|segment| segment.starts_with("unk") || segment.contains("_unk"))
+}
+
+/// A serializer that calls `visit` with the path and value of every scalar.
+struct Walker<F> {
+    path: String,
+    visit: F,
+}
+
+#[derive(Debug)]
+struct WalkError(String);
+
+impl fmt::Display for WalkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for WalkError {}
+
+impl ser::Error for WalkError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        WalkError(msg.to_string())
+    }
+}
+
+impl<F: FnMut(&str, Value)> Walker<F> {
+    fn scalar(&mut self, value: Value) -> Result<(), WalkError> {
+        (self.visit)(&self.path, value);
+        Ok(())
+    }
+
+    /// Serializes `value` with `segment` appended to the path.
+    fn nested<T: Serialize + ?Sized>(&mut self, segment: &str, value: &T) -> Result<(), WalkError> {
+        let len = self.path.len();
+        if !self.path.is_empty() && !segment.starts_with('[') {
+            self.path.push('.');
+        }
+        self.path.push_str(segment);
+        let res = value.serialize(&mut *self);
+        self.path.truncate(len);
+        res
+    }
+
+    /// Appends the name of a struct or variant to the path, unless it already names the field.
+    fn enter(&mut self, name: &str) -> usize {
+        let len = self.path.len();
+        if self.path.is_empty() {
+            self.path.push_str(name);
+        }
+        len
+    }
+}
+
+/// Walks the elements of a compound value, `index` counts tuple elements.
+struct Compound<'w, F> {
+    walker: &'w mut Walker<F>,
+    /// The length of the path to restore once the compound is done.
+    len: usize,
+    index: usize,
+    tuple: bool,
+}
+
+impl<'w, F: FnMut(&str, Value)> Compound<'w, F> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), WalkError> {
+        if self.tuple {
+            let segment = self.index.to_string();
+            self.index += 1;
+            self.walker.nested(&segment, value)
+        } else {
+            self.walker.nested("[]", value)
+        }
+    }
+
+    fn end(self) -> Result<(), WalkError> {
+        self.walker.path.truncate(self.len);
+        Ok(())
+    }
+}
+
+impl<'w, F: FnMut(&str, Value)> ser::Serializer for &'w mut Walker<F> {
+    type Ok = ();
+    type Error = WalkError;
+    type SerializeSeq = Compound<'w, F>;
+    type SerializeTuple = Compound<'w, F>;
+    type SerializeTupleStruct = Compound<'w, F>;
+    type SerializeTupleVariant = Compound<'w, F>;
+    type SerializeMap = Compound<'w, F>;
+    type SerializeStruct = Compound<'w, F>;
+    type SerializeStructVariant = Compound<'w, F>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), WalkError> {
+        self.scalar(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), WalkError> {
+        self.scalar(Value::Int(v.into()))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), WalkError> {
+        self.scalar(Value::Int(v.into()))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), WalkError> {
+        self.scalar(Value::Int(v.into()))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), WalkError> {
+        self.scalar(Value::Int(v.into()))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), WalkError> {
+        self.scalar(Value::Int(v.into()))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), WalkError> {
+        self.scalar(Value::Int(v.into()))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), WalkError> {
+        self.scalar(Value::Int(v.into()))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), WalkError> {
+        self.scalar(Value::Int(v.into()))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), WalkError> {
+        self.scalar(Value::Float(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), WalkError> {
+        self.scalar(Value::Float(v as f32))
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), WalkError> {
+        self.scalar(Value::Str(v.to_string().into()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), WalkError> {
+        self.scalar(Value::Str(v.into()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), WalkError> {
+        v.iter().try_for_each(|byte| self.nested("[]", byte))
+    }
+
+    fn serialize_none(self) -> Result<(), WalkError> {
+        self.scalar(Value::None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), WalkError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), WalkError> {
+        self.scalar(Value::None)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), WalkError> {
+        self.scalar(Value::None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<(), WalkError> {
+        self.scalar(Value::Str(variant.into()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), WalkError> {
+        let len = self.enter(name);
+        let res = value.serialize(&mut *self);
+        self.path.truncate(len);
+        res
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), WalkError> {
+        self.nested(variant, value)
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Compound<'w, F>, WalkError> {
+        Ok(Compound {
+            len: self.path.len(),
+            walker: self,
+            index: 0,
+            tuple: false,
+        })
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Compound<'w, F>, WalkError> {
+        Ok(Compound {
+            len: self.path.len(),
+            walker: self,
+            index: 0,
+            tuple: true,
+        })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        _: usize,
+    ) -> Result<Compound<'w, F>, WalkError> {
+        Ok(Compound {
+            len: self.enter(name),
+            walker: self,
+            index: 0,
+            tuple: true,
+        })
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Compound<'w, F>, WalkError> {
+        let len = self.path.len();
+        if !self.path.is_empty() {
+            self.path.push('.');
+        }
+        self.path.push_str(variant);
+        Ok(Compound {
+            len,
+            walker: self,
+            index: 0,
+            tuple: true,
+        })
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Compound<'w, F>, WalkError> {
+        self.serialize_seq(None)
+    }
+
+    fn serialize_struct(self, name: &'static str, _: usize) -> Result<Compound<'w, F>, WalkError> {
+        Ok(Compound {
+            len: self.enter(name),
+            walker: self,
+            index: 0,
+            tuple: false,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Compound<'w, F>, WalkError> {
+        self.serialize_tuple_variant(name, index, variant, len)
+    }
+}
+
+impl<F: FnMut(&str, Value)> ser::SerializeSeq for Compound<'_, F> {
+    type Ok = ();
+    type Error = WalkError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), WalkError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), WalkError> {
+        Compound::end(self)
+    }
+}
+
+impl<F: FnMut(&str, Value)> ser::SerializeTuple for Compound<'_, F> {
+    type Ok = ();
+    type Error = WalkError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), WalkError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), WalkError> {
+        Compound::end(self)
+    }
+}
+
+impl<F: FnMut(&str, Value)> ser::SerializeTupleStruct for Compound<'_, F> {
+    type Ok = ();
+    type Error = WalkError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), WalkError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), WalkError> {
+        Compound::end(self)
+    }
+}
+
+impl<F: FnMut(&str, Value)> ser::SerializeTupleVariant for Compound<'_, F> {
+    type Ok = ();
+    type Error = WalkError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), WalkError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), WalkError> {
+        Compound::end(self)
+    }
+}
+
+impl<F: FnMut(&str, Value)> ser::SerializeMap for Compound<'_, F> {
+    type Ok = ();
+    type Error = WalkError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, _: &T) -> Result<(), WalkError> {
+        // map entries are addressed like sequence elements, their keys are not part of the path
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), WalkError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), WalkError> {
+        Compound::end(self)
+    }
+}
+
+impl<F: FnMut(&str, Value)> ser::SerializeStruct for Compound<'_, F> {
+    type Ok = ();
+    type Error = WalkError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), WalkError> {
+        self.walker.nested(key, value)
+    }
+
+    fn end(self) -> Result<(), WalkError> {
+        Compound::end(self)
+    }
+}
+
+impl<F: FnMut(&str, Value)> ser::SerializeStructVariant for Compound<'_, F> {
+    type Ok = ();
+    type Error = WalkError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), WalkError> {
+        self.walker.nested(key, value)
+    }
+
+    fn end(self) -> Result<(), WalkError> {
+        Compound::end(self)
+    }
+}
+
+#[test]
+fn unknown_field_statistics() {
+    use crate::jmxvbms::ClothSimParams;
+
+    let params = |unk0, unk1, unk8| ClothSimParams {
+        unk0,
+        unk1,
+        unk2: 0.0,
+        unk3: 0.0,
+        unk4: 0.0,
+        unk5: 0.0,
+        unk6: 0.0,
+        unk7: 0.0,
+        unk8,
+    };
+    let mut stats = UnknownStats::new();
+    stats.correlate("ClothSimParams.unk0", "ClothSimParams.unk8");
+    stats.add(&params(1, 0.5, 0x3F80_0000));
+    stats.add(&params(4, 2.0, 0x3F00_0000));
+    stats.add(&params(0, 2.0, 0x3FC0_0000));
+
+    let unk0 = stats.field("ClothSimParams.unk0").unwrap();
+    assert_eq!((unk0.count, unk0.files), (3, 3));
+    assert_eq!((unk0.min, unk0.max), (Some(0.0), Some(4.0)));
+    assert_eq!(unk0.guess(), Some(Shape::Flag));
+    let unk1 = stats.field("ClothSimParams.unk1").unwrap();
+    assert_eq!(unk1.distinct[&Value::Float(2.0)], 2);
+    assert_eq!(unk1.guess(), Some(Shape::Float));
+    // u32 fields whose bits look like floats
+    assert_eq!(
+        stats.field("ClothSimParams.unk8").unwrap().guess(),
+        Some(Shape::Float)
+    );
+    assert_eq!(
+        stats.field("ClothSimParams.unk2").unwrap().guess(),
+        Some(Shape::Zero)
+    );
+
+    let table = stats
+        .correlation("ClothSimParams.unk0", "ClothSimParams.unk8")
+        .unwrap();
+    assert_eq!(table.pairs.len(), 3);
+    assert_eq!(table.pairs[&(Value::Int(4), Value::Int(0x3F00_0000))], 1);
+    assert_eq!(table.by_presence()[&(Value::Int(0), true)], 1);
+}
+
+#[test]
+fn field_paths() {
+    let mut paths = Vec::new();
+    let mut walker = Walker {
+        path: String::new(),
+        visit: |path: &str, _| paths.push(path.to_owned()),
+    };
+    let file = crate::AnyFile::ObjectIfo(vec![crate::jmxvobji::ObjectIfo {
+        index: 1,
+        flag: 0,
+        path: std::path::Path::new("a.bsr").into(),
+    }]);
+    file.serialize(&mut walker).unwrap();
+    let mesh_paths = vec![(Box::<str>::from("a.bms"), Some(1u32))];
+    walker.path.push_str("JmxRes.mesh_paths");
+    mesh_paths.serialize(&mut walker).unwrap();
+    assert_eq!(
+        paths,
+        [
+            "ObjectIfo[].index",
+            "ObjectIfo[].flag",
+            "ObjectIfo[].path",
+            "JmxRes.mesh_paths[].0",
+            "JmxRes.mesh_paths[].1",
+        ]
+    );
+}
@@ -0,0 +1,83 @@
+//! The counterparts of [`parser_ext`](crate::parser_ext) for serializing files again.
+
+use std::io::{self, Write};
+
+pub mod number {
+    use mint::{Vector2, Vector3};
+
+    use super::*;
+
+    #[inline]
+    pub fn write_u8<W: Write>(w: &mut W, v: u8) -> io::Result<()> {
+        w.write_all(&[v])
+    }
+
+    #[inline]
+    pub fn write_u16<W: Write>(w: &mut W, v: u16) -> io::Result<()> {
+        w.write_all(&v.to_le_bytes())
+    }
+
+    #[inline]
+    pub fn write_u32<W: Write>(w: &mut W, v: u32) -> io::Result<()> {
+        w.write_all(&v.to_le_bytes())
+    }
+
+    #[inline]
+    pub fn write_i32<W: Write>(w: &mut W, v: i32) -> io::Result<()> {
+        w.write_all(&v.to_le_bytes())
+    }
+
+    #[inline]
+    pub fn write_f32<W: Write>(w: &mut W, v: f32) -> io::Result<()> {
+        w.write_all(&v.to_le_bytes())
+    }
+
+    /// Writes a Vector2<f32>
+    #[inline]
+    pub fn write_vector2_f32<W: Write>(w: &mut W, v: Vector2<f32>) -> io::Result<()> {
+        write_f32(w, v.x)?;
+        write_f32(w, v.y)
+    }
+
+    /// Writes a Vector3<f32>
+    #[inline]
+    pub fn write_vector3_f32<W: Write>(w: &mut W, v: Vector3<f32>) -> io::Result<()> {
+        write_f32(w, v.x)?;
+        write_f32(w, v.y)?;
+        write_f32(w, v.z)
+    }
+
+    /// Writes a [f32; 6] array
+    #[inline]
+    pub fn write_vector6_f32<W: Write>(w: &mut W, v: &[f32; 6]) -> io::Result<()> {
+        v.iter().try_for_each(|&f| write_f32(w, f))
+    }
+}
+
+pub mod string {
+    use super::number::write_u32;
+    use super::*;
+
+    /// Writes the EUC_KR encoded string prefixed by its byte length as a u32
+    #[inline]
+    pub fn write_sized_string<W: Write>(w: &mut W, s: &str) -> io::Result<()> {
+        let bytes = encoding_rs::EUC_KR.encode(s).0;
+        write_u32(w, bytes.len() as u32)?;
+        w.write_all(&bytes)
+    }
+}
+
+pub mod multi {
+    use super::number::write_u32;
+    use super::*;
+
+    /// Writes the amount of objects as a u32 followed by the objects themselves
+    pub fn write_objects_u32<W: Write, T>(
+        w: &mut W,
+        objects: &[T],
+        mut f: impl FnMut(&mut W, &T) -> io::Result<()>,
+    ) -> io::Result<()> {
+        write_u32(w, objects.len() as u32)?;
+        objects.iter().try_for_each(|o| f(w, o))
+    }
+}